@@ -0,0 +1,136 @@
+//! Reads the command line options the game, the terminal front-end and
+//! the simulator share, so that they accept the same values.
+
+use game_2048_engine::{BlockSize, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+
+/// Deeper searches take seconds per move.
+pub const MAX_SOLVER_DEPTH: u8 = 4;
+
+/// Parses a `--size` value, `<cols>x<rows>` like `5x4`, with both sides
+/// from `MIN_BOARD_SIZE` to `MAX_BOARD_SIZE`.
+pub fn parse_board_size(value: &str) -> Result<(u8, u8), String> {
+    let invalid_size = || format!(
+        "invalid size: {}, expected <cols>x<rows> between {} and {}",
+        value, MIN_BOARD_SIZE, MAX_BOARD_SIZE,
+    );
+
+    let mut parts = value.split('x');
+
+    let width = parts.next().and_then(|part| part.parse::<u8>().ok());
+    let height = parts.next().and_then(|part| part.parse::<u8>().ok());
+
+    match (width, height, parts.next()) {
+        (Some(width), Some(height), None) => {
+            let range = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+
+            if range.contains(&width) && range.contains(&height) {
+                Ok((width, height))
+            } else {
+                Err(invalid_size())
+            }
+        },
+        _ => Err(invalid_size()),
+    }
+}
+
+/// Parses a `--seed` value.
+pub fn parse_seed(value: &str) -> Result<u64, String> {
+    value.parse::<u64>().map_err(|_| format!("invalid seed: {}, expected a number", value))
+}
+
+/// Parses a `--spawn` value, `<value>:<weight>,...` like `2:9,4:1`, into a
+/// distribution for `SpawnPolicy::new`, which checks the weights.
+pub fn parse_spawn_distribution(value: &str) -> Result<Vec<(BlockSize, u32)>, String> {
+    let invalid_distribution = || format!(
+        "invalid spawn distribution: {}, expected <value>:<weight>,... like 2:9,4:1",
+        value,
+    );
+
+    let mut distribution = Vec::new();
+
+    for entry in value.split(',') {
+        let mut parts = entry.split(':');
+
+        let block_size = parts.next()
+            .and_then(|part| part.parse::<u32>().ok())
+            .and_then(BlockSize::from_value);
+        let weight = parts.next().and_then(|part| part.parse::<u32>().ok());
+
+        match (block_size, weight, parts.next()) {
+            (Some(block_size), Some(weight), None) => distribution.push((block_size, weight)),
+            _ => return Err(invalid_distribution()),
+        }
+    }
+
+    Ok(distribution)
+}
+
+/// Parses a `--spawn-per-move` or `--initial-blocks` value for
+/// `SpawnPolicy::new`, which checks it is positive.
+pub fn parse_block_count(value: &str) -> Result<u8, String> {
+    value.parse::<u8>().map_err(|_| format!("invalid block count: {}, expected a number", value))
+}
+
+/// Parses a `--solver-depth` value, from 1 to `MAX_SOLVER_DEPTH`.
+pub fn parse_solver_depth(value: &str) -> Result<u8, String> {
+    value.parse::<u8>()
+        .ok()
+        .filter(|depth| (1..=MAX_SOLVER_DEPTH).contains(depth))
+        .ok_or(format!("invalid solver depth: {}, expected 1 to {}", value, MAX_SOLVER_DEPTH))
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+
+    #[test]
+    fn board_sizes() {
+        assert_eq!(parse_board_size("4x4"), Ok((4, 4)));
+        assert_eq!(parse_board_size("5x3"), Ok((5, 3)));
+        assert_eq!(parse_board_size("3x8"), Ok((MIN_BOARD_SIZE, MAX_BOARD_SIZE)));
+
+        for value in ["2x4", "4x9", "4", "4x4x4", "four", "x4", ""].iter() {
+            assert!(parse_board_size(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn seeds() {
+        assert_eq!(parse_seed("2048"), Ok(2048));
+        assert_eq!(parse_seed("18446744073709551615"), Ok(u64::MAX));
+
+        assert!(parse_seed("-1").is_err());
+        assert!(parse_seed("seed").is_err());
+    }
+
+    #[test]
+    fn spawn_distributions() {
+        assert_eq!(
+            parse_spawn_distribution("2:9,4:1"),
+            Ok(vec![(BlockSize::MIN, 9), (BlockSize::from_value(4).unwrap(), 1)]),
+        );
+        assert_eq!(parse_spawn_distribution("8:0,16:2"), Ok(vec![(BlockSize::from_value(8).unwrap(), 0), (BlockSize::from_value(16).unwrap(), 2)]));
+        assert_eq!(parse_spawn_distribution("2:0"), Ok(vec![(BlockSize::MIN, 0)]));
+        assert_eq!(
+            parse_spawn_distribution("2:4294967295,4:1"),
+            Ok(vec![(BlockSize::MIN, u32::MAX), (BlockSize::from_value(4).unwrap(), 1)]),
+        );
+
+        for value in ["3:1", "2", "2:1:1", "2:-1", "2:4294967296", ""].iter() {
+            assert!(parse_spawn_distribution(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn counts_and_depths() {
+        assert_eq!(parse_block_count("2"), Ok(2));
+        assert_eq!(parse_block_count("0"), Ok(0));
+        assert!(parse_block_count("-1").is_err());
+        assert!(parse_block_count("many").is_err());
+
+        assert_eq!(parse_solver_depth("1"), Ok(1));
+        assert_eq!(parse_solver_depth("4"), Ok(MAX_SOLVER_DEPTH));
+        assert!(parse_solver_depth("0").is_err());
+        assert!(parse_solver_depth("9").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use game_2048_engine::{Bitboard, GameBoard, GameMovementDirection, MoveTables, SpawnPolicy};
+
+/// Boards from a few seeded games, so both representations move the same
+/// mix of sparse and crowded boards.
+fn sample_boards() -> Vec<GameBoard> {
+    let spawn_policy = SpawnPolicy::default();
+    let mut boards = Vec::new();
+
+    for seed in 0..10 {
+        let mut game_board = GameBoard::with_seed(4, 4, seed);
+
+        game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        for direction in GameMovementDirection::ALL.iter().cycle().take(100) {
+            if game_board.apply_move(*direction).changed() {
+                game_board.spawn(&spawn_policy, spawn_policy.blocks_per_move);
+            }
+
+            boards.push(game_board.clone());
+        }
+    }
+
+    boards
+}
+
+fn moves(c: &mut Criterion) {
+    let boards = sample_boards();
+    let bitboards: Vec<Bitboard> = boards.iter().map(|board| Bitboard::from_game_board(board).unwrap()).collect();
+    let tables = MoveTables::new();
+
+    let mut group = c.benchmark_group("moves");
+
+    // every board is moved in all four directions
+    group.throughput(Throughput::Elements(boards.len() as u64 * 4));
+
+    group.bench_function("game_board", |b| b.iter(|| {
+        for game_board in boards.iter() {
+            for direction in GameMovementDirection::ALL.iter() {
+                black_box(game_board.clone().apply_move(*direction));
+            }
+        }
+    }));
+
+    group.bench_function("bitboard", |b| b.iter(|| {
+        for bitboard in bitboards.iter() {
+            for direction in GameMovementDirection::ALL.iter() {
+                let mut moved = *bitboard;
+
+                black_box(moved.apply_move(&tables, *direction).ok());
+            }
+        }
+    }));
+
+    group.finish();
+}
+
+fn tables(c: &mut Criterion) {
+    c.bench_function("move_tables", |b| b.iter(MoveTables::new));
+}
+
+criterion_group!(benches, moves, tables);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+#![no_main]
+use std::collections::HashSet;
+use std::convert::TryInto;
+
+use game_2048_engine::{GameBoard, GameMovementDirection, SpawnPolicy};
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the board size, the next eight the seed, every
+// other byte a move, each followed by the spawns a real game makes.
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 9 {
+        return;
+    }
+
+    let (width, height) = (2 + data[0] % 7, 2 + data[0] / 7 % 7);
+    let seed = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let spawn_policy = SpawnPolicy::default();
+    let mut game_board = GameBoard::with_seed(width, height, seed);
+
+    game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+    for byte in data[9..].iter() {
+        let direction = GameMovementDirection::ALL[usize::from(byte % 4)];
+        let legal = game_board.legal_moves().contains(&direction);
+        let (score, moves) = (game_board.score(), game_board.moves());
+
+        let outcome = game_board.apply_move(direction);
+
+        assert_eq!(outcome.changed(), legal);
+        assert_eq!(game_board.score(), score + outcome.points);
+        assert_eq!(game_board.moves(), moves + u32::from(outcome.changed()));
+        assert!(outcome.merges.iter().all(|merge| game_board.get_tile(merge.to.0, merge.to.1).map(|tile| tile.id) == Some(merge.id)));
+
+        if outcome.changed() {
+            game_board.spawn(&spawn_policy, spawn_policy.blocks_per_move);
+        }
+
+        let mut ids = HashSet::new();
+
+        for y in 0..height {
+            for x in 0..width {
+                if let Some(tile) = game_board.get_tile(x, y) {
+                    assert!(tile.id < game_board.next_tile_id());
+                    assert!(ids.insert(tile.id), "tile {:?} is on the board twice", tile.id);
+                }
+            }
+        }
+
+        assert_eq!(game_board.is_over(), game_board.legal_moves().is_empty());
+    }
+});
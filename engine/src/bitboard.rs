@@ -0,0 +1,428 @@
+use crate::block::BlockSize;
+use crate::direction::GameMovementDirection;
+use crate::game_board::GameBoard;
+
+/// The largest exponent a cell holds in its 4 bits, the 32768 block.
+/// Two of them would merge into a 65536, so `Bitboard::apply_move`
+/// refuses the moves that merge them.
+pub const BITBOARD_MAX_EXPONENT: u8 = 15;
+
+const ROWS: usize = 1 << 16;
+
+/// A 4x4 board packed into 64 bits: 4 bits per cell holding the block
+/// exponent, `0` for an empty cell. Row `y` takes bits `16 * y` to
+/// `16 * y + 15`, cell `x` of a row the 4 bits from `4 * x`.
+///
+/// Built for simulations that make millions of moves: a move is a table
+/// lookup per row, see `MoveTables`. Score, moves and the generator stay
+/// with `GameBoard`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const WIDTH: u8 = 4;
+    pub const HEIGHT: u8 = 4;
+
+    /// Packs the cells of a 4x4 board with no block above 32768.
+    pub fn from_game_board(game_board: &GameBoard) -> Result<Self, String> {
+        if game_board.width() != Bitboard::WIDTH || game_board.height() != Bitboard::HEIGHT {
+            return Err(format!("unsupported board size: {}x{}", game_board.width(), game_board.height()));
+        }
+
+        let mut bitboard = Bitboard::default();
+
+        for y in 0..Bitboard::HEIGHT {
+            for x in 0..Bitboard::WIDTH {
+                let cell = game_board.get_cell(x, y);
+
+                match cell {
+                    Some(block_size) if block_size.exponent() > BITBOARD_MAX_EXPONENT => {
+                        return Err(format!("block too large for a bitboard: {}", block_size));
+                    },
+                    _ => bitboard.set_cell(x, y, cell),
+                }
+            }
+        }
+
+        Ok(bitboard)
+    }
+
+    /// A board with these cells and a fresh score, spawning from `seed`.
+    pub fn to_game_board(self, seed: u64) -> GameBoard {
+        let mut game_board = GameBoard::with_seed(Bitboard::WIDTH, Bitboard::HEIGHT, seed);
+
+        for y in 0..Bitboard::HEIGHT {
+            for x in 0..Bitboard::WIDTH {
+                game_board.set_cell(x, y, self.get_cell(x, y));
+            }
+        }
+
+        game_board
+    }
+
+    fn shift(x: u8, y: u8) -> u64 {
+        u64::from(y) * 16 + u64::from(x) * 4
+    }
+
+    pub fn get_cell(self, x: u8, y: u8) -> Option<BlockSize> {
+        BlockSize::try_from_exponent(((self.0 >> Bitboard::shift(x, y)) & 0xf) as u8)
+    }
+
+    /// Panics on blocks above 32768, which do not fit in the 4 bits of a
+    /// cell: `from_game_board` turns them into an error instead.
+    pub fn set_cell(&mut self, x: u8, y: u8, block: Option<BlockSize>) {
+        let exponent = block.map_or(0, BlockSize::exponent);
+
+        assert!(exponent <= BITBOARD_MAX_EXPONENT, "block too large for a bitboard: {}", block.unwrap());
+
+        let exponent = u64::from(exponent);
+        let shift = Bitboard::shift(x, y);
+
+        self.0 = (self.0 & !(0xf << shift)) | (exponent << shift);
+    }
+
+    pub fn available_cells(self) -> u32 {
+        (0..16).filter(|cell| (self.0 >> (cell * 4)) & 0xf == 0).count() as u32
+    }
+
+    pub fn max_block(self) -> Option<BlockSize> {
+        (0..16)
+            .map(|cell| ((self.0 >> (cell * 4)) & 0xf) as u8)
+            .max()
+            .and_then(BlockSize::try_from_exponent)
+    }
+
+    /// Mirrors the board over its main diagonal, so columns become rows.
+    pub fn transpose(self) -> Self {
+        let x = self.0;
+
+        let a1 = x & 0xf0f0_0f0f_f0f0_0f0f;
+        let a2 = x & 0x0000_f0f0_0000_f0f0;
+        let a3 = x & 0x0f0f_0000_0f0f_0000;
+        let a = a1 | (a2 << 12) | (a3 >> 12);
+
+        let b1 = a & 0xff00_ff00_00ff_00ff;
+        let b2 = a & 0x00ff_00ff_0000_0000;
+        let b3 = a & 0x0000_0000_ff00_ff00;
+
+        Bitboard(b1 | (b2 >> 24) | (b3 << 24))
+    }
+
+    fn row(self, y: usize) -> u16 {
+        (self.0 >> (y * 16)) as u16
+    }
+
+    fn map_rows(self, table: &[u16]) -> Self {
+        Bitboard((0..4).fold(0, |board, y| board | u64::from(table[self.row(y) as usize]) << (y * 16)))
+    }
+
+    /// Moves the board like `GameBoard::apply_move` and returns the points
+    /// earned, `None` when the move changes nothing. Where the blocks went
+    /// is not tracked. A move that merges two 32768 blocks is an error and
+    /// leaves the board as it was, as 65536 does not fit in a cell.
+    pub fn apply_move(&mut self, tables: &MoveTables, direction: GameMovementDirection) -> Result<Option<u32>, String> {
+        let vertical = matches!(direction, GameMovementDirection::Up | GameMovementDirection::Down);
+        // columns are moved as the rows of the transposed board
+        let rows = if vertical { self.transpose() } else { *self };
+
+        if tables.overflows(rows) {
+            return Err(format!(
+                "moving {:?} merges two {} blocks, too large for a bitboard",
+                direction,
+                BlockSize::from_exponent(BITBOARD_MAX_EXPONENT),
+            ));
+        }
+
+        let moved = match direction {
+            GameMovementDirection::Left | GameMovementDirection::Up => rows.map_rows(&tables.left),
+            GameMovementDirection::Right | GameMovementDirection::Down => rows.map_rows(&tables.right),
+        };
+        let moved = if vertical { moved.transpose() } else { moved };
+
+        if moved == *self {
+            return Ok(None);
+        }
+
+        *self = moved;
+
+        Ok(Some(tables.points(rows)))
+    }
+
+    /// The directions that change the board, including those `apply_move`
+    /// refuses because they merge two 32768 blocks, as for a `GameBoard`.
+    pub fn legal_moves(self, tables: &MoveTables) -> Vec<GameMovementDirection> {
+        GameMovementDirection::ALL.iter()
+            .copied()
+            .filter(|direction| {
+                let mut moved = self;
+
+                moved.apply_move(tables, *direction) != Ok(None)
+            })
+            .collect()
+    }
+}
+
+/// The result of moving each of the 65536 possible rows left and right,
+/// the points the merges in it earn and whether it merges two 32768
+/// blocks. Both directions merge the same pairs, so they share the points.
+pub struct MoveTables {
+    left: Vec<u16>,
+    right: Vec<u16>,
+    points: Vec<u32>,
+    overflows: Vec<bool>,
+}
+
+impl MoveTables {
+    pub fn new() -> Self {
+        let mut tables = MoveTables {
+            left: vec![0; ROWS],
+            right: vec![0; ROWS],
+            points: vec![0; ROWS],
+            overflows: vec![false; ROWS],
+        };
+
+        for row in 0..ROWS {
+            let cells = unpack_row(row as u16);
+
+            let (moved, points) = match move_cells_left(cells) {
+                Some(moved) => moved,
+                None => {
+                    tables.overflows[row] = true;
+                    continue;
+                },
+            };
+
+            tables.left[row] = pack_row(moved);
+            tables.points[row] = points;
+
+            let mut reversed = cells;
+            reversed.reverse();
+
+            // a row merging two 32768 blocks one way also does the other way
+            let (mut moved, _) = move_cells_left(reversed).expect("no overflow to the right either");
+            moved.reverse();
+
+            tables.right[row] = pack_row(moved);
+        }
+
+        tables
+    }
+
+    fn points(&self, bitboard: Bitboard) -> u32 {
+        (0..4).map(|y| self.points[bitboard.row(y) as usize]).sum()
+    }
+
+    fn overflows(&self, bitboard: Bitboard) -> bool {
+        (0..4).any(|y| self.overflows[bitboard.row(y) as usize])
+    }
+}
+
+impl Default for MoveTables {
+    fn default() -> Self {
+        MoveTables::new()
+    }
+}
+
+fn unpack_row(row: u16) -> [u8; 4] {
+    let mut cells = [0; 4];
+
+    for (x, cell) in cells.iter_mut().enumerate() {
+        *cell = ((row >> (x * 4)) & 0xf) as u8;
+    }
+
+    cells
+}
+
+fn pack_row(cells: [u8; 4]) -> u16 {
+    cells.iter()
+        .enumerate()
+        .fold(0, |row, (x, exponent)| row | u16::from(*exponent) << (x * 4))
+}
+
+/// Slides the exponents of a row towards its first cell, merging each
+/// pair of equal blocks once. `None` when two 32768 blocks would merge.
+fn move_cells_left(cells: [u8; 4]) -> Option<([u8; 4], u32)> {
+    let mut moved = [0; 4];
+    let mut points = 0;
+    let mut target = 0;
+    let mut mergeable = false;
+
+    for exponent in cells.iter().copied().filter(|exponent| *exponent > 0) {
+        if mergeable && moved[target - 1] == exponent {
+            if exponent == BITBOARD_MAX_EXPONENT {
+                return None;
+            }
+
+            moved[target - 1] += 1;
+            points += 1 << moved[target - 1];
+            mergeable = false;
+        } else {
+            moved[target] = exponent;
+            target += 1;
+            mergeable = true;
+        }
+    }
+
+    Some((moved, points))
+}
+
+#[cfg(test)]
+mod bitboard_tests {
+    use std::ops::RangeInclusive;
+
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::game_board::GameRng;
+
+    fn random_board(rng: &mut GameRng, exponents: RangeInclusive<u8>) -> GameBoard {
+        let mut game_board = GameBoard::with_seed(4, 4, 0);
+
+        for y in 0..4 {
+            for x in 0..4 {
+                let exponent = if rng.gen_bool(0.3) { 0 } else { rng.gen_range(exponents.clone()) };
+
+                game_board.set_cell(x, y, BlockSize::try_from_exponent(exponent));
+            }
+        }
+
+        game_board
+    }
+
+    #[test]
+    fn cells() {
+        let mut bitboard = Bitboard::default();
+
+        bitboard.set_cell(1, 2, BlockSize::from_value(8));
+        bitboard.set_cell(3, 3, BlockSize::from_value(32768));
+
+        assert_eq!(bitboard.0, 0xf000_0030_0000_0000);
+        assert_eq!(bitboard.get_cell(1, 2), BlockSize::from_value(8));
+        assert_eq!(bitboard.get_cell(0, 0), None);
+        assert_eq!(bitboard.available_cells(), 14);
+        assert_eq!(bitboard.max_block(), BlockSize::from_value(32768));
+
+        bitboard.set_cell(3, 3, None);
+
+        assert_eq!(bitboard.0, 0x0000_0030_0000_0000);
+    }
+
+    #[test]
+    #[should_panic(expected = "block too large for a bitboard: 65536")]
+    fn oversized_cell() {
+        Bitboard::default().set_cell(0, 0, BlockSize::from_value(65536));
+    }
+
+    #[test]
+    fn conversions() {
+        let mut rng = GameRng::seed_from_u64(1);
+        let game_board = random_board(&mut rng, 1..=4);
+        let bitboard = Bitboard::from_game_board(&game_board).unwrap();
+
+        assert_eq!(bitboard.to_game_board(0).pretty_string(), game_board.pretty_string());
+
+        let mut large = GameBoard::with_seed(4, 4, 0);
+        large.set_cell(0, 0, BlockSize::from_value(65536));
+
+        assert!(Bitboard::from_game_board(&large).is_err());
+        assert!(Bitboard::from_game_board(&GameBoard::with_seed(5, 4, 0)).is_err());
+    }
+
+    #[test]
+    fn transpose() {
+        let mut rng = GameRng::seed_from_u64(2);
+        let bitboard = Bitboard(rng.gen());
+        let transposed = bitboard.transpose();
+
+        for y in 0..4 {
+            for x in 0..4 {
+                assert_eq!(transposed.get_cell(x, y), bitboard.get_cell(y, x));
+            }
+        }
+
+        assert_eq!(transposed.transpose(), bitboard);
+    }
+
+    #[test]
+    fn rows() {
+        assert_eq!(move_cells_left([1, 1, 1, 1]), Some(([2, 2, 0, 0], 8)));
+        assert_eq!(move_cells_left([0, 2, 0, 2]), Some(([3, 0, 0, 0], 8)));
+        assert_eq!(move_cells_left([2, 1, 1, 0]), Some(([2, 2, 0, 0], 4)));
+        assert_eq!(move_cells_left([3, 2, 1, 0]), Some(([3, 2, 1, 0], 0)));
+        assert_eq!(move_cells_left([15, 14, 14, 0]), Some(([15, 15, 0, 0], 32768)));
+        assert_eq!(move_cells_left([15, 0, 15, 0]), None);
+    }
+
+    #[test]
+    fn merges_once() {
+        let tables = MoveTables::new();
+        let mut bitboard = Bitboard(0x1110);
+
+        assert_eq!(bitboard.apply_move(&tables, GameMovementDirection::Right), Ok(Some(4)));
+        assert_eq!(bitboard, Bitboard(0x2100));
+    }
+
+    /// Every move of a bitboard matches the same move of a `GameBoard`,
+    /// except those making a 65536, which the bitboard refuses.
+    fn assert_matches_game_board(tables: &MoveTables, game_board: &GameBoard) {
+        let bitboard = Bitboard::from_game_board(game_board).unwrap();
+
+        for direction in GameMovementDirection::ALL.iter().copied() {
+            let mut expected = game_board.clone();
+            let mut moved = bitboard;
+
+            let outcome = expected.apply_move(direction);
+            let result = moved.apply_move(tables, direction);
+
+            if expected.max_block() > Some(BlockSize::from_exponent(BITBOARD_MAX_EXPONENT)) {
+                assert!(result.is_err(), "{:?}\n{}", direction, game_board.pretty_string());
+                assert_eq!(moved, bitboard);
+                continue;
+            }
+
+            let expected_points = Some(outcome.points).filter(|_| outcome.changed());
+
+            assert_eq!(result, Ok(expected_points), "{:?}\n{}", direction, game_board.pretty_string());
+            assert_eq!(
+                moved.to_game_board(0).pretty_string(),
+                expected.pretty_string(),
+                "{:?}\n{}", direction, game_board.pretty_string(),
+            );
+        }
+
+        assert_eq!(bitboard.legal_moves(tables), game_board.legal_moves());
+    }
+
+    #[test]
+    fn matches_game_board() {
+        let tables = MoveTables::new();
+        let mut rng = GameRng::seed_from_u64(3);
+
+        // small exponents so that neighbours often merge
+        for _ in 0..2000 {
+            assert_matches_game_board(&tables, &random_board(&mut rng, 1..=4));
+        }
+
+        // the largest blocks, where two 32768 blocks may meet
+        for _ in 0..500 {
+            assert_matches_game_board(&tables, &random_board(&mut rng, 13..=15));
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let tables = MoveTables::new();
+        let mut game_board = GameBoard::with_seed(4, 4, 0);
+
+        game_board.set_cell(0, 0, BlockSize::from_value(32768));
+        game_board.set_cell(3, 0, BlockSize::from_value(32768));
+        game_board.set_cell(0, 1, BlockSize::from_value(16384));
+
+        let mut bitboard = Bitboard::from_game_board(&game_board).unwrap();
+
+        assert!(bitboard.apply_move(&tables, GameMovementDirection::Left).is_err());
+        assert_eq!(bitboard, Bitboard::from_game_board(&game_board).unwrap());
+        assert_eq!(bitboard.apply_move(&tables, GameMovementDirection::Down), Ok(Some(0)));
+        assert_matches_game_board(&tables, &game_board);
+    }
+}
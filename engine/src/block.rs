@@ -0,0 +1,106 @@
+use std::fmt;
+
+/// The value of a block, stored as the power of two it shows:
+/// the 2 block has exponent 1, the 2048 block has exponent 11.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BlockSize(u8);
+
+pub const WIN_BLOCK_SIZE: BlockSize = BlockSize::from_exponent(11);
+
+impl BlockSize {
+    /// The largest exponent whose value still fits in a `u32`.
+    pub const MAX_EXPONENT: u8 = 31;
+
+    pub const MIN: BlockSize = BlockSize(1);
+
+    /// The exponent has to be from 1 to `MAX_EXPONENT`, others make `value`
+    /// overflow. Outside the engine, exponents go through `try_from_exponent`.
+    pub(crate) const fn from_exponent(exponent: u8) -> Self {
+        BlockSize(exponent)
+    }
+
+    /// The block with this exponent, `None` for 0 or past `MAX_EXPONENT`.
+    pub fn try_from_exponent(exponent: u8) -> Option<Self> {
+        if (1..=BlockSize::MAX_EXPONENT).contains(&exponent) {
+            Some(BlockSize(exponent))
+        } else {
+            None
+        }
+    }
+
+    pub fn from_value(value: u32) -> Option<Self> {
+        if value < 2 || !value.is_power_of_two() {
+            return None;
+        }
+
+        Some(BlockSize(value.trailing_zeros() as u8))
+    }
+
+    pub fn exponent(self) -> u8 {
+        self.0
+    }
+
+    pub fn value(self) -> u32 {
+        1 << self.0
+    }
+
+    pub fn next(self) -> Option<Self> {
+        if self.0 < BlockSize::MAX_EXPONENT {
+            Some(BlockSize(self.0 + 1))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the block two blocks merge into, if they can be merged.
+    pub fn merge(self, other: BlockSize) -> Option<Self> {
+        if self == other {
+            self.next()
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for BlockSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+#[cfg(test)]
+mod block_tests {
+    use super::*;
+
+    #[test]
+    fn values() {
+        assert_eq!(BlockSize::MIN.value(), 2);
+        assert_eq!(BlockSize::MIN.to_string(), "2");
+        assert_eq!(WIN_BLOCK_SIZE.value(), 2048);
+        assert_eq!(BlockSize::from_exponent(17).to_string(), "131072");
+        assert_eq!(BlockSize::from_exponent(BlockSize::MAX_EXPONENT).value(), 1 << 31);
+
+        assert_eq!(BlockSize::from_value(4096), Some(BlockSize::from_exponent(12)));
+        assert_eq!(BlockSize::from_value(1), None);
+        assert_eq!(BlockSize::from_value(12), None);
+
+        assert_eq!(BlockSize::try_from_exponent(1), Some(BlockSize::MIN));
+        assert_eq!(BlockSize::try_from_exponent(BlockSize::MAX_EXPONENT), BlockSize::from_value(1 << 31));
+        assert_eq!(BlockSize::try_from_exponent(0), None);
+        assert_eq!(BlockSize::try_from_exponent(BlockSize::MAX_EXPONENT + 1), None);
+    }
+
+    #[test]
+    fn merging() {
+        let block = BlockSize::from_exponent(16);
+
+        assert_eq!(block.merge(block), Some(BlockSize::from_exponent(17)));
+        assert_eq!(block.merge(BlockSize::MIN), None);
+
+        let max_block = BlockSize::from_exponent(BlockSize::MAX_EXPONENT);
+
+        assert_eq!(max_block.merge(max_block), None);
+
+        assert!(BlockSize::MIN < WIN_BLOCK_SIZE);
+    }
+}
@@ -0,0 +1,17 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameMovementDirection {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl GameMovementDirection {
+    pub const ALL: [GameMovementDirection; 4] = [
+        GameMovementDirection::Up,
+        GameMovementDirection::Right,
+        GameMovementDirection::Down,
+        GameMovementDirection::Left,
+    ];
+}
@@ -0,0 +1,1225 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::block::BlockSize;
+use crate::direction::GameMovementDirection;
+use crate::move_outcome::{MoveOutcome, TileMerge, TileMotion};
+use crate::spawn_policy::SpawnPolicy;
+use crate::tile::{Tile, TileId};
+
+/// Board cells stored row by row: `game_board_array[y][x]`.
+pub type GameBoardArray = Vec<Vec<Option<Tile>>>;
+
+/// The generator used to place new blocks. ChaCha gives the same sequence
+/// on every platform, so a seed can be shared between players.
+pub type GameRng = ChaCha8Rng;
+
+/// The smallest board the front-ends accept on either side.
+pub const MIN_BOARD_SIZE: u8 = 3;
+/// The largest board the front-ends accept on either side.
+pub const MAX_BOARD_SIZE: u8 = 8;
+
+/// Everything a move can change, so a move can be taken back
+/// and replayed with the same blocks spawned after it.
+#[derive(Clone)]
+pub struct GameSnapshot {
+    game_board_array: GameBoardArray,
+    next_tile_id: TileId,
+    score: u32,
+    moves: u32,
+    rng: GameRng,
+}
+
+#[derive(Clone)]
+pub struct GameBoard {
+    width: u8,
+    height: u8,
+    game_board_array: GameBoardArray,
+    /// The ID the next new block gets.
+    next_tile_id: TileId,
+    score: u32,
+    best_score: u32,
+    moves: u32,
+    seed: u64,
+    rng: GameRng,
+}
+
+impl GameBoard {
+    pub fn new(width: u8, height: u8) -> Self {
+        GameBoard::with_seed(width, height, rand::random())
+    }
+
+    pub fn with_seed(width: u8, height: u8, seed: u64) -> Self {
+        GameBoard {
+            width,
+            height,
+            game_board_array: GameBoard::empty_array(width, height),
+            next_tile_id: TileId::FIRST,
+            score: 0,
+            best_score: 0,
+            moves: 0,
+            seed,
+            rng: GameRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Clears the board and the score, keeping the best score.
+    pub fn reset(&mut self) {
+        self.game_board_array = GameBoard::empty_array(self.width, self.height);
+        self.next_tile_id = TileId::FIRST;
+        self.score = 0;
+        self.moves = 0;
+    }
+
+    /// Brings back the counters of a saved game and moves the generator
+    /// to the point the game stopped at. The blocks are set tile by tile.
+    pub fn resume(&mut self, score: u32, best_score: u32, moves: u32, rng_position: u128, next_tile_id: TileId) {
+        self.next_tile_id = self.next_tile_id.max(next_tile_id);
+        self.score = score;
+        self.best_score = best_score.max(score);
+        self.moves = moves;
+        self.rng.set_word_pos(rng_position);
+    }
+
+    /// Keeps a best score reached in an earlier game.
+    pub fn record_best_score(&mut self, best_score: u32) {
+        self.best_score = self.best_score.max(best_score);
+    }
+
+    /// How far the generator is into the stream of its seed, in 32-bit words.
+    pub fn rng_position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            game_board_array: self.game_board_array.clone(),
+            next_tile_id: self.next_tile_id,
+            score: self.score,
+            moves: self.moves,
+            rng: self.rng.clone(),
+        }
+    }
+
+    /// Brings back the board, the score and the RNG state of a snapshot.
+    /// The best score is kept, it only ever grows.
+    pub fn restore(&mut self, snapshot: &GameSnapshot) {
+        self.game_board_array = snapshot.game_board_array.clone();
+        self.next_tile_id = snapshot.next_tile_id;
+        self.score = snapshot.score;
+        self.moves = snapshot.moves;
+        self.rng = snapshot.rng.clone();
+    }
+
+    /// The same game on a board of the given size, every block moved to
+    /// `cell(x, y)` with its ID.
+    pub(crate) fn remapped(&self, width: u8, height: u8, cell: impl Fn(u8, u8) -> (u8, u8)) -> GameBoard {
+        let mut game_board_array = GameBoard::empty_array(width, height);
+
+        for (y, row) in self.game_board_array.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let (new_x, new_y) = cell(x as u8, y as u8);
+
+                game_board_array[new_y as usize][new_x as usize] = *tile;
+            }
+        }
+
+        GameBoard {
+            width,
+            height,
+            game_board_array,
+            ..self.clone()
+        }
+    }
+
+    fn empty_array(width: u8, height: u8) -> GameBoardArray {
+        vec![vec![None; width as usize]; height as usize]
+    }
+
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    pub fn height(&self) -> u8 {
+        self.height
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn best_score(&self) -> u32 {
+        self.best_score
+    }
+
+    pub fn moves(&self) -> u32 {
+        self.moves
+    }
+
+    pub fn get_cell(&self, x: u8, y: u8) -> Option<BlockSize> {
+        self.get_tile(x, y).map(|tile| tile.block_size)
+    }
+
+    /// Puts a new block, with an ID of its own, in a cell.
+    pub fn set_cell(&mut self, x: u8, y: u8, block: Option<BlockSize>) {
+        let tile = block.map(|block_size| Tile {
+            id: self.new_tile_id(),
+            block_size,
+        });
+
+        self.set_tile(x, y, tile);
+    }
+
+    pub fn get_tile(&self, x: u8, y: u8) -> Option<Tile> {
+        self.game_board_array[y as usize][x as usize]
+    }
+
+    /// Puts a tile in a cell as it is. New blocks never take its ID.
+    pub fn set_tile(&mut self, x: u8, y: u8, tile: Option<Tile>) {
+        if let Some(tile) = tile {
+            self.next_tile_id = self.next_tile_id.max(tile.id.next());
+        }
+
+        self.game_board_array[y as usize][x as usize] = tile;
+    }
+
+    pub fn next_tile_id(&self) -> TileId {
+        self.next_tile_id
+    }
+
+    fn new_tile_id(&mut self) -> TileId {
+        let id = self.next_tile_id;
+
+        self.next_tile_id = id.next();
+
+        id
+    }
+
+    pub fn pretty_string(&self) -> String {
+        let mut result = String::from("\n");
+
+        for arr in self.game_board_array.iter() {
+            result.push('|');
+
+            for cell in arr.iter() {
+                if let Some(tile) = cell {
+                    result += &format!(" {} ", tile.block_size)[..];
+                } else {
+                    result += " - "
+                }
+
+                result += "|";
+            }
+
+            result += "\n";
+        }
+
+        result
+    }
+
+    /// How many cells the block at `x, y` slides when moved in `direction`,
+    /// 0 for an empty cell.
+    pub fn steps(&self, x: u8, y: u8, direction: GameMovementDirection) -> u8 {
+        let line = match direction {
+            GameMovementDirection::Up | GameMovementDirection::Down => x,
+            GameMovementDirection::Left | GameMovementDirection::Right => y,
+        };
+        let cells = self.line_cells(direction, line);
+        let index = match cells.iter().position(|cell| *cell == (x, y)) {
+            Some(index) => index,
+            None => return 0,
+        };
+
+        match compress_line(&self.line_blocks(&cells))[index] {
+            Some(target) => (index - target) as u8,
+            None => 0,
+        }
+    }
+
+    pub fn max_block(&self) -> Option<BlockSize> {
+        self.game_board_array.iter()
+            .flat_map(|row| row.iter())
+            .filter_map(|cell| cell.map(|tile| tile.block_size))
+            .max()
+    }
+
+    /// Tells whether the board is full and has no blocks left to merge.
+    pub fn is_over(&self) -> bool {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let cell = self.get_cell(x, y);
+
+                if cell.is_none() {
+                    return false;
+                }
+
+                if x + 1 < self.width && cell == self.get_cell(x + 1, y) {
+                    return false;
+                }
+
+                if y + 1 < self.height && cell == self.get_cell(x, y + 1) {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// The directions that move at least one block.
+    pub fn legal_moves(&self) -> Vec<GameMovementDirection> {
+        GameMovementDirection::ALL.iter()
+            .copied()
+            .filter(|direction| {
+                (0..self.line_count(*direction)).any(|line| {
+                    let targets = compress_line(&self.line_blocks(&self.line_cells(*direction, line)));
+
+                    targets.iter().enumerate().any(|(index, target)| matches!(target, Some(target) if *target != index))
+                })
+            })
+            .collect()
+    }
+
+    /// Picks one of the empty cells with equal probability,
+    /// or returns `None` when the board is full.
+    pub fn rand_available_cell(&mut self) -> Option<(u8, u8)> {
+        let available_cells = self.available_cells();
+
+        if available_cells.is_empty() {
+            return None;
+        }
+
+        let index = self.rng.gen_range(0..available_cells.len());
+
+        Some(available_cells[index])
+    }
+
+    /// Places up to `count` blocks picked by the spawn policy on random empty
+    /// cells and returns them with their positions.
+    pub fn spawn(&mut self, spawn_policy: &SpawnPolicy, count: u8) -> Vec<(BlockSize, (u8, u8))> {
+        let mut result = Vec::new();
+
+        for _ in 0..count {
+            let (x, y) = match self.rand_available_cell() {
+                Some(cell) => cell,
+                None => break,
+            };
+
+            let block_size = spawn_policy.pick_block(&mut self.rng);
+
+            self.set_cell(x, y, Some(block_size));
+
+            result.push((block_size, (x, y)));
+        }
+
+        result
+    }
+
+    pub fn available_cells(&self) -> Vec<(u8, u8)> {
+        let mut result = Vec::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get_cell(x, y).is_none() {
+                    result.push((x, y));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Moves the board and returns where every block went. A move that
+    /// changes nothing is not made and counts as no move.
+    pub fn apply_move(&mut self, direction: GameMovementDirection) -> MoveOutcome {
+        let mut outcome = MoveOutcome::new(direction);
+
+        for line in 0..self.line_count(direction) {
+            let cells = self.line_cells(direction, line);
+
+            self.move_line(&cells, &mut outcome);
+        }
+
+        if !outcome.changed() {
+            return outcome;
+        }
+
+        self.score += outcome.points;
+        self.best_score = self.best_score.max(self.score);
+        self.moves += 1;
+
+        outcome
+    }
+
+    /// How many rows or columns a move in `direction` slides blocks along.
+    fn line_count(&self, direction: GameMovementDirection) -> u8 {
+        match direction {
+            GameMovementDirection::Up | GameMovementDirection::Down => self.width,
+            GameMovementDirection::Left | GameMovementDirection::Right => self.height,
+        }
+    }
+
+    /// The cells of a row or column, starting at the edge the blocks move towards.
+    fn line_cells(&self, direction: GameMovementDirection, line: u8) -> Vec<(u8, u8)> {
+        match direction {
+            GameMovementDirection::Up => (0..self.height).map(|y| (line, y)).collect(),
+            GameMovementDirection::Down => (0..self.height).rev().map(|y| (line, y)).collect(),
+            GameMovementDirection::Left => (0..self.width).map(|x| (x, line)).collect(),
+            GameMovementDirection::Right => (0..self.width).rev().map(|x| (x, line)).collect(),
+        }
+    }
+
+    fn line_blocks(&self, cells: &[(u8, u8)]) -> Vec<Option<BlockSize>> {
+        cells.iter().map(|(x, y)| self.get_cell(*x, *y)).collect()
+    }
+
+    /// Slides one row or column and writes it back, recording motions and
+    /// merges. The block nearer the edge keeps its ID when two merge.
+    fn move_line(&mut self, cells: &[(u8, u8)], outcome: &mut MoveOutcome) {
+        let tiles: Vec<Option<Tile>> = cells.iter().map(|(x, y)| self.get_tile(*x, *y)).collect();
+        let targets = compress_line(&self.line_blocks(cells));
+        let mut moved: Vec<Option<(Tile, (u8, u8))>> = vec![None; cells.len()];
+
+        for (index, (tile, target)) in tiles.into_iter().zip(targets).enumerate() {
+            let (tile, target) = match (tile, target) {
+                (Some(tile), Some(target)) => (tile, target),
+                _ => continue,
+            };
+
+            if target != index {
+                outcome.motions.push(TileMotion {
+                    id: tile.id,
+                    from: cells[index],
+                    to: cells[target],
+                });
+            }
+
+            moved[target] = match moved[target] {
+                Some((kept, source)) => {
+                    let block_size = kept.block_size.next().expect("compress_line only merges blocks that can grow");
+
+                    outcome.points += block_size.value();
+                    outcome.merges.push(TileMerge {
+                        id: kept.id,
+                        merged_id: tile.id,
+                        sources: [source, cells[index]],
+                        to: cells[target],
+                        block_size,
+                    });
+
+                    Some((Tile { id: kept.id, block_size }, source))
+                },
+                None => Some((tile, cells[index])),
+            };
+        }
+
+        for ((x, y), tile) in cells.iter().zip(moved) {
+            self.set_tile(*x, *y, tile.map(|(tile, _)| tile));
+        }
+    }
+}
+
+/// Where every block of a line ends up when the line slides towards its
+/// first cell, as indexes into the line. Two blocks with the same target
+/// merge. A block merges at most once per move, so `[2, 2, 2, 2]` gives
+/// `[4, 4]`, and the block a merge produces does not merge again, so
+/// `[4, 4, 8]` gives `[8, 8]`.
+fn compress_line(line: &[Option<BlockSize>]) -> Vec<Option<usize>> {
+    let mut targets = vec![None; line.len()];
+    let mut next_target = 0;
+    // the last block placed, while it can still take a merge
+    let mut open: Option<(usize, BlockSize)> = None;
+
+    for (index, cell) in line.iter().enumerate() {
+        let block_size = match cell {
+            Some(block_size) => *block_size,
+            None => continue,
+        };
+
+        match open {
+            Some((target, open_block)) if open_block.merge(block_size).is_some() => {
+                targets[index] = Some(target);
+                open = None;
+            },
+            _ => {
+                targets[index] = Some(next_target);
+                open = Some((next_target, block_size));
+                next_target += 1;
+            },
+        }
+    }
+
+    targets
+}
+
+#[cfg(test)]
+mod game_board_tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::block::*;
+
+    fn block(value: u32) -> BlockSize {
+        BlockSize::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn steps_plain() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        game_board.set_cell(0, 0, Some(block(2)));
+
+        println!("{}", game_board.pretty_string());
+
+        assert!(game_board.get_cell(0, 0).is_some());
+        assert!(game_board.get_cell(0, 3).is_none());
+
+        game_board.apply_move(GameMovementDirection::Down);
+
+        println!("{}", game_board.pretty_string());
+
+        assert!(game_board.get_cell(0, 0).is_none());
+        assert!(game_board.get_cell(0, 3).is_some());
+    }
+
+    #[test]
+    fn steps_multi_blocks() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        game_board.set_cell(0, 0, Some(block(2)));
+        game_board.set_cell(0, 1, Some(block(4)));
+
+        println!("{}", game_board.pretty_string());
+
+        assert!(game_board.get_cell(0, 0).is_some());
+        assert!(game_board.get_cell(0, 1).is_some());
+        assert!(game_board.get_cell(0, 3).is_none());
+        assert!(game_board.get_cell(0, 2).is_none());
+
+        game_board.apply_move(GameMovementDirection::Down);
+
+        println!("{}", game_board.pretty_string());
+
+        assert!(game_board.get_cell(0, 0).is_none());
+        assert!(game_board.get_cell(0, 1).is_none());
+        assert!(game_board.get_cell(0, 3).is_some());
+        assert!(game_board.get_cell(0, 2).is_some());
+
+        assert_eq!(game_board.get_cell(0, 3).unwrap(), block(4));
+        assert_eq!(game_board.get_cell(0, 2).unwrap(), block(2));
+    }
+
+    #[test]
+    fn blocks_merging() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        game_board.set_cell(0, 0, Some(block(2)));
+        game_board.set_cell(0, 1, Some(block(2)));
+
+        println!("{}", game_board.pretty_string());
+
+        assert!(game_board.get_cell(0, 0).is_some());
+        assert!(game_board.get_cell(0, 1).is_some());
+        assert!(game_board.get_cell(0, 3).is_none());
+        assert!(game_board.get_cell(0, 2).is_none());
+
+        game_board.apply_move(GameMovementDirection::Down);
+
+        println!("{}", game_board.pretty_string());
+
+        assert!(game_board.get_cell(0, 0).is_none());
+        assert!(game_board.get_cell(0, 1).is_none());
+        assert!(game_board.get_cell(0, 2).is_none());
+        assert!(game_board.get_cell(0, 3).is_some());
+
+        assert_eq!(game_board.get_cell(0, 3).unwrap(), block(4));
+    }
+
+    #[test]
+    fn complex_blocks_merging() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        game_board.set_cell(0, 3, Some(block(2)));
+        game_board.set_cell(1, 3, Some(block(2)));
+        game_board.set_cell(2, 3, Some(block(4)));
+
+        println!("{}", game_board.pretty_string());
+
+        assert!(game_board.get_cell(0, 3).is_some());
+        assert!(game_board.get_cell(1, 3).is_some());
+        assert!(game_board.get_cell(2, 3).is_some());
+
+        game_board.apply_move(GameMovementDirection::Left);
+
+        println!("{}", game_board.pretty_string());
+
+        assert!(game_board.get_cell(0, 3).is_some());
+        assert!(game_board.get_cell(1, 3).is_some());
+        assert!(game_board.get_cell(2, 3).is_none());
+
+        assert_eq!(game_board.get_cell(0, 3).unwrap(), block(4));
+        assert_eq!(game_board.get_cell(1, 3).unwrap(), block(4));
+    }
+
+    #[test]
+    fn square_board_sizes() {
+        for size in [3, 5, 6, 8].iter().cloned() {
+            let mut game_board = GameBoard::new(size, size);
+
+            game_board.set_cell(0, 0, Some(block(2)));
+            game_board.set_cell(0, size - 1, Some(block(2)));
+
+            game_board.apply_move(GameMovementDirection::Down);
+
+            assert_eq!(game_board.get_cell(0, size - 1), Some(block(4)));
+
+            game_board.apply_move(GameMovementDirection::Right);
+
+            assert_eq!(game_board.get_cell(size - 1, size - 1), Some(block(4)));
+
+            game_board.apply_move(GameMovementDirection::Up);
+            game_board.apply_move(GameMovementDirection::Left);
+
+            assert_eq!(game_board.get_cell(0, 0), Some(block(4)));
+            assert_eq!(game_board.max_block(), Some(block(4)));
+        }
+    }
+
+    /// A block given as `(x, y, value)`.
+    type Cell = (u8, u8, u32);
+
+    fn board_from(width: u8, height: u8, cells: &[Cell]) -> GameBoard {
+        let mut game_board = GameBoard::new(width, height);
+
+        for (x, y, value) in cells.iter().cloned() {
+            game_board.set_cell(x, y, Some(block(value)));
+        }
+
+        game_board
+    }
+
+    fn cells(game_board: &GameBoard) -> Vec<Cell> {
+        let mut result = Vec::new();
+
+        for y in 0..game_board.height() {
+            for x in 0..game_board.width() {
+                if let Some(block_size) = game_board.get_cell(x, y) {
+                    result.push((x, y, block_size.value()));
+                }
+            }
+        }
+
+        result
+    }
+
+    fn assert_moves(
+        width: u8,
+        height: u8,
+        initial: &[Cell],
+        expected: &[(GameMovementDirection, &[Cell])],
+    ) {
+        for (direction, expected_cells) in expected.iter() {
+            let mut game_board = board_from(width, height, initial);
+
+            game_board.apply_move(*direction);
+
+            println!("{:?}: {}", direction, game_board.pretty_string());
+
+            assert_eq!(cells(&game_board), cells(&board_from(width, height, expected_cells)));
+        }
+    }
+
+    #[test]
+    fn rectangular_tall_board() {
+        assert_moves(
+            4,
+            6,
+            &[(0, 0, 2), (2, 1, 2), (3, 1, 2), (3, 2, 4), (0, 5, 2), (1, 5, 8)],
+            &[
+                (GameMovementDirection::Up, &[(0, 0, 4), (1, 0, 8), (2, 0, 2), (3, 0, 2), (3, 1, 4)]),
+                (GameMovementDirection::Down, &[(3, 4, 2), (0, 5, 4), (1, 5, 8), (2, 5, 2), (3, 5, 4)]),
+                (GameMovementDirection::Left, &[(0, 0, 2), (0, 1, 4), (0, 2, 4), (0, 5, 2), (1, 5, 8)]),
+                (GameMovementDirection::Right, &[(3, 0, 2), (3, 1, 4), (3, 2, 4), (2, 5, 2), (3, 5, 8)]),
+            ],
+        );
+    }
+
+    #[test]
+    fn rectangular_wide_board() {
+        assert_moves(
+            5,
+            3,
+            &[(0, 0, 2), (4, 0, 2), (3, 1, 4), (4, 1, 4), (1, 2, 8)],
+            &[
+                (GameMovementDirection::Up, &[(0, 0, 2), (1, 0, 8), (3, 0, 4), (4, 0, 2), (4, 1, 4)]),
+                (GameMovementDirection::Down, &[(4, 1, 2), (0, 2, 2), (1, 2, 8), (3, 2, 4), (4, 2, 4)]),
+                (GameMovementDirection::Left, &[(0, 0, 4), (0, 1, 8), (0, 2, 8)]),
+                (GameMovementDirection::Right, &[(4, 0, 4), (4, 1, 8), (4, 2, 8)]),
+            ],
+        );
+    }
+
+    #[test]
+    fn rectangular_rand_cells() {
+        for (width, height) in [(3, 5), (5, 3), (4, 6)].iter().cloned() {
+            let mut game_board = GameBoard::new(width, height);
+
+            for _ in 0..(width * height) {
+                let (x, y) = game_board.rand_available_cell().unwrap();
+
+                assert!(x < width && y < height);
+                assert!(game_board.get_cell(x, y).is_none());
+
+                game_board.set_cell(x, y, Some(block(2)));
+            }
+
+            assert_eq!(game_board.rand_available_cell(), None);
+        }
+    }
+
+    #[test]
+    fn seeded_games_repeat() {
+        let directions = [
+            GameMovementDirection::Left,
+            GameMovementDirection::Down,
+            GameMovementDirection::Right,
+            GameMovementDirection::Up,
+        ];
+
+        let play = |seed: u64| {
+            let mut game_board = GameBoard::with_seed(4, 4, seed);
+            let mut boards = Vec::new();
+
+            for direction in directions.iter().cycle().take(40).cloned() {
+                if let Some((x, y)) = game_board.rand_available_cell() {
+                    game_board.set_cell(x, y, Some(block(2)));
+                }
+
+                game_board.apply_move(direction);
+
+                boards.push(cells(&game_board));
+            }
+
+            boards
+        };
+
+        assert_eq!(GameBoard::with_seed(4, 4, 42).seed(), 42);
+        assert_eq!(play(42), play(42));
+        assert_eq!(play(2048), play(2048));
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        let mut game_board = GameBoard::with_seed(4, 4, 3);
+        let spawn_policy = SpawnPolicy::default();
+
+        game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        let snapshot = game_board.snapshot();
+        let initial_cells = cells(&game_board);
+
+        let play = |game_board: &mut GameBoard| {
+            game_board.apply_move(GameMovementDirection::Left);
+            game_board.spawn(&spawn_policy, spawn_policy.blocks_per_move);
+            game_board.apply_move(GameMovementDirection::Down);
+            game_board.spawn(&spawn_policy, spawn_policy.blocks_per_move);
+
+            (cells(game_board), game_board.score())
+        };
+
+        let first_play = play(&mut game_board);
+
+        game_board.restore(&snapshot);
+
+        assert_eq!(cells(&game_board), initial_cells);
+        assert_eq!(game_board.score(), 0);
+        assert_eq!(game_board.moves(), 0);
+        assert_eq!(play(&mut game_board), first_play);
+    }
+
+    #[test]
+    fn merge_points() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        game_board.set_cell(0, 3, Some(block(2)));
+        game_board.set_cell(1, 3, Some(block(2)));
+        game_board.set_cell(2, 3, Some(block(4)));
+        game_board.set_cell(3, 3, Some(block(4)));
+
+        assert_eq!(game_board.apply_move(GameMovementDirection::Left).points, 12);
+        assert_eq!(game_board.score(), 12);
+
+        assert!(!game_board.apply_move(GameMovementDirection::Left).changed());
+        assert_eq!(game_board.score(), 12);
+        assert_eq!(game_board.moves(), 1);
+
+        game_board.set_cell(0, 0, Some(block(8)));
+        game_board.set_cell(3, 0, Some(block(8)));
+
+        assert_eq!(game_board.apply_move(GameMovementDirection::Left).points, 16);
+        assert_eq!(game_board.score(), 28);
+        assert_eq!(game_board.best_score(), 28);
+    }
+
+    #[test]
+    fn move_outcome() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        game_board.set_cell(1, 0, Some(block(2)));
+        game_board.set_cell(3, 0, Some(block(2)));
+        game_board.set_cell(0, 2, Some(block(8)));
+        game_board.set_cell(2, 2, Some(block(4)));
+
+        let outcome = game_board.apply_move(GameMovementDirection::Left);
+
+        assert!(outcome.changed());
+        assert_eq!(outcome.direction, GameMovementDirection::Left);
+        assert_eq!(outcome.points, 4);
+
+        // the 8 block is already against the edge
+        assert_eq!(outcome.motions, vec![
+            TileMotion { id: TileId(1), from: (1, 0), to: (0, 0) },
+            TileMotion { id: TileId(2), from: (3, 0), to: (0, 0) },
+            TileMotion { id: TileId(4), from: (2, 2), to: (1, 2) },
+        ]);
+        assert_eq!(outcome.merges, vec![TileMerge {
+            id: TileId(1),
+            merged_id: TileId(2),
+            sources: [(1, 0), (3, 0)],
+            to: (0, 0),
+            block_size: block(4),
+        }]);
+
+        let outcome = game_board.apply_move(GameMovementDirection::Down);
+
+        assert_eq!(outcome.merges, vec![]);
+        assert_eq!(outcome.motions, vec![
+            TileMotion { id: TileId(3), from: (0, 2), to: (0, 3) },
+            TileMotion { id: TileId(1), from: (0, 0), to: (0, 2) },
+            TileMotion { id: TileId(4), from: (1, 2), to: (1, 3) },
+        ]);
+
+        let outcome = game_board.apply_move(GameMovementDirection::Down);
+
+        assert_eq!(outcome, MoveOutcome::new(GameMovementDirection::Down));
+        assert!(!outcome.changed());
+    }
+
+    #[test]
+    fn merges_once() {
+        assert_moves(
+            4,
+            3,
+            &[(0, 0, 2), (1, 0, 2), (2, 0, 2), (3, 0, 2), (0, 1, 4), (1, 1, 4), (2, 1, 8), (0, 2, 2), (2, 2, 2), (3, 2, 2)],
+            &[
+                (GameMovementDirection::Left, &[(0, 0, 4), (1, 0, 4), (0, 1, 8), (1, 1, 8), (0, 2, 4), (1, 2, 2)]),
+                (GameMovementDirection::Right, &[(2, 0, 4), (3, 0, 4), (2, 1, 8), (3, 1, 8), (2, 2, 2), (3, 2, 4)]),
+            ],
+        );
+
+        let mut game_board = board_from(4, 1, &[(0, 0, 2), (1, 0, 2), (2, 0, 2), (3, 0, 2)]);
+        let outcome = game_board.apply_move(GameMovementDirection::Left);
+
+        assert_eq!(outcome.points, 8);
+        assert_eq!(
+            outcome.merges.iter().map(|merge| (merge.id, merge.merged_id, merge.sources, merge.to)).collect::<Vec<_>>(),
+            vec![
+                (TileId(1), TileId(2), [(0, 0), (1, 0)], (0, 0)),
+                (TileId(3), TileId(4), [(2, 0), (3, 0)], (1, 0)),
+            ],
+        );
+    }
+
+    #[test]
+    fn steps() {
+        let game_board = board_from(4, 1, &[(0, 0, 2), (2, 0, 2), (3, 0, 2)]);
+
+        assert_eq!(game_board.steps(0, 0, GameMovementDirection::Right), 2);
+        assert_eq!(game_board.steps(2, 0, GameMovementDirection::Right), 1);
+        assert_eq!(game_board.steps(3, 0, GameMovementDirection::Right), 0);
+        assert_eq!(game_board.steps(3, 0, GameMovementDirection::Left), 2);
+        assert_eq!(game_board.steps(1, 0, GameMovementDirection::Left), 0);
+        assert_eq!(game_board.steps(0, 0, GameMovementDirection::Down), 0);
+    }
+
+    /// The textbook rule on plain values, 0 for an empty cell: drop the gaps,
+    /// then merge equal neighbours from the front, each block at most once.
+    fn reference_move(line: [u32; 4]) -> ([u32; 4], u32) {
+        let blocks: Vec<u32> = line.iter().copied().filter(|value| *value > 0).collect();
+        let mut moved = [0; 4];
+        let mut points = 0;
+        let (mut index, mut target) = (0, 0);
+
+        while index < blocks.len() {
+            if index + 1 < blocks.len() && blocks[index] == blocks[index + 1] {
+                moved[target] = blocks[index] * 2;
+                points += moved[target];
+                index += 2;
+            } else {
+                moved[target] = blocks[index];
+                index += 1;
+            }
+
+            target += 1;
+        }
+
+        (moved, points)
+    }
+
+    /// Every line of empty cells and blocks up to 32, put in every row or
+    /// column of a 4x4 board and moved in every direction, ends up as the
+    /// reference rule says, with the blocks that did not merge away keeping
+    /// their IDs.
+    #[test]
+    fn matches_reference() {
+        const VALUES: [u32; 6] = [0, 2, 4, 8, 16, 32];
+
+        for code in 0..VALUES.len().pow(4) {
+            let mut line = [0; 4];
+
+            for (position, value) in line.iter_mut().enumerate() {
+                *value = VALUES[code / VALUES.len().pow(position as u32) % VALUES.len()];
+            }
+
+            let (expected, expected_points) = reference_move(line);
+
+            for direction in GameMovementDirection::ALL.iter().copied() {
+                for index in 0..4 {
+                    // the cell `position` of the line, counted from the edge the blocks move towards
+                    let cell = |position: u8| match direction {
+                        GameMovementDirection::Up => (index, position),
+                        GameMovementDirection::Down => (index, 3 - position),
+                        GameMovementDirection::Left => (position, index),
+                        GameMovementDirection::Right => (3 - position, index),
+                    };
+
+                    let mut game_board = GameBoard::new(4, 4);
+
+                    for (position, value) in line.iter().enumerate() {
+                        let (x, y) = cell(position as u8);
+
+                        game_board.set_cell(x, y, BlockSize::from_value(*value));
+                    }
+
+                    let ids_before = tile_id_set(&game_board);
+                    let outcome = game_board.apply_move(direction);
+                    let moved: Vec<u32> = (0..4)
+                        .map(|position| {
+                            let (x, y) = cell(position);
+
+                            game_board.get_cell(x, y).map_or(0, |block_size| block_size.value())
+                        })
+                        .collect();
+
+                    assert_eq!(moved, expected.to_vec(), "{:?} {:?} line {}", line, direction, index);
+                    assert_eq!(outcome.points, expected_points, "{:?} {:?}", line, direction);
+                    assert_eq!(outcome.changed(), line != expected, "{:?} {:?}", line, direction);
+
+                    let merged_ids: HashSet<TileId> = outcome.merges.iter().map(|merge| merge.merged_id).collect();
+
+                    assert_eq!(tile_id_set(&game_board), &ids_before - &merged_ids, "{:?} {:?}", line, direction);
+                }
+            }
+        }
+    }
+
+    fn tile_id_set(game_board: &GameBoard) -> HashSet<TileId> {
+        (0..game_board.height())
+            .flat_map(|y| (0..game_board.width()).map(move |x| (x, y)))
+            .filter_map(|(x, y)| game_board.get_tile(x, y).map(|tile| tile.id))
+            .collect()
+    }
+
+    #[test]
+    fn tile_ids() {
+        let spawn_policy = SpawnPolicy::default();
+        let mut game_board = GameBoard::with_seed(4, 4, 2);
+
+        game_board.set_cell(0, 0, Some(block(2)));
+        game_board.set_cell(3, 0, Some(block(4)));
+
+        assert_eq!(game_board.get_tile(0, 0), Some(Tile { id: TileId(1), block_size: block(2) }));
+
+        let snapshot = game_board.snapshot();
+
+        // a block keeps its ID wherever it moves
+        game_board.apply_move(GameMovementDirection::Down);
+
+        assert_eq!(game_board.get_tile(0, 3).map(|tile| tile.id), Some(TileId(1)));
+        assert_eq!(game_board.get_tile(3, 3).map(|tile| tile.id), Some(TileId(2)));
+
+        let (_, (x, y)) = game_board.spawn(&spawn_policy, 1)[0];
+
+        assert_eq!(game_board.get_tile(x, y).map(|tile| tile.id), Some(TileId(3)));
+        assert_eq!(game_board.next_tile_id(), TileId(4));
+
+        // undone blocks take their IDs with them, the blocks spawned again get the same ones
+        game_board.restore(&snapshot);
+
+        assert_eq!(game_board.next_tile_id(), TileId(3));
+
+        game_board.set_tile(1, 1, Some(Tile { id: TileId(10), block_size: block(8) }));
+
+        assert_eq!(game_board.next_tile_id(), TileId(11));
+
+        game_board.reset();
+
+        assert_eq!(game_board.next_tile_id(), TileId::FIRST);
+    }
+
+    #[test]
+    fn legal_moves() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        let blocks = [block(2), block(4)];
+
+        for y in 0..game_board.height() {
+            for x in 0..game_board.width() {
+                game_board.set_cell(x, y, Some(blocks[((x + y) % 2) as usize]));
+            }
+        }
+
+        assert!(game_board.is_over());
+        assert!(game_board.legal_moves().is_empty());
+
+        game_board.set_cell(1, 0, Some(block(2)));
+
+        assert!(!game_board.is_over());
+        assert_eq!(game_board.legal_moves(), GameMovementDirection::ALL.to_vec());
+
+        game_board.set_cell(1, 0, None);
+
+        // nothing can fall into the gap along the top edge
+        assert!(!game_board.is_over());
+        assert_eq!(game_board.legal_moves(), vec![
+            GameMovementDirection::Up,
+            GameMovementDirection::Right,
+            GameMovementDirection::Left,
+        ]);
+
+        game_board.reset();
+
+        assert!(!game_board.is_over());
+        assert!(game_board.legal_moves().is_empty());
+        assert!(game_board.get_cell(0, 0).is_none());
+    }
+
+    #[test]
+    fn merging_past_2048() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        game_board.set_cell(0, 0, Some(block(2048)));
+        game_board.set_cell(1, 0, Some(block(2048)));
+        game_board.set_cell(0, 1, Some(block(1024)));
+
+        assert_eq!(game_board.max_block(), Some(block(2048)));
+
+        assert_eq!(game_board.apply_move(GameMovementDirection::Left).points, 4096);
+
+        assert_eq!(game_board.get_cell(0, 0), Some(block(4096)));
+        assert_eq!(game_board.max_block(), Some(block(4096)));
+    }
+
+    #[test]
+    fn rand_cells() {
+        let mut game_board = GameBoard::new(4, 4);
+
+        for _x in 0..game_board.height() {
+            for _y in 0..game_board.width() {
+                println!("testing: ({}, {})", _x, _y);
+
+                let (x, y) = game_board.rand_available_cell().unwrap();
+                assert!(game_board.get_cell(x, y).is_none());
+
+                game_board.set_cell(x, y, Some(block(2)));
+            }
+        }
+
+        assert_eq!(game_board.rand_available_cell(), None);
+    }
+
+    #[test]
+    fn spawning_blocks() {
+        let mut game_board = GameBoard::with_seed(3, 3, 1);
+        let spawn_policy = SpawnPolicy::default();
+
+        let spawned = game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        assert_eq!(spawned.len(), 2);
+
+        for (block_size, (x, y)) in spawned.iter().cloned() {
+            assert!(block_size == block(2) || block_size == block(4));
+            assert_eq!(game_board.get_cell(x, y), Some(block_size));
+        }
+
+        assert_eq!(game_board.spawn(&spawn_policy, 10).len(), 7);
+        assert!(game_board.spawn(&spawn_policy, 1).is_empty());
+    }
+
+    #[test]
+    fn rand_cells_uniform() {
+        let mut game_board = GameBoard::with_seed(4, 4, 7);
+
+        // leave the first and the last cell free, a biased scan would favour one
+        for y in 0..game_board.height() {
+            for x in 0..game_board.width() {
+                game_board.set_cell(x, y, Some(block(2)));
+            }
+        }
+
+        game_board.set_cell(0, 0, None);
+        game_board.set_cell(3, 3, None);
+
+        let mut first_cell_picks = 0;
+
+        for _ in 0..10000 {
+            match game_board.rand_available_cell() {
+                Some((0, 0)) => first_cell_picks += 1,
+                Some((3, 3)) => {},
+                cell => panic!("picked a taken cell: {:?}", cell),
+            }
+        }
+
+        assert!((4500..5500).contains(&first_cell_picks), "first cell picked {} times", first_cell_picks);
+    }
+}
+
+#[cfg(test)]
+mod game_board_properties {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::symmetry::Symmetry;
+
+    /// A board as its size and one exponent per cell, row by row, 0 for an
+    /// empty cell. Small exponents so that neighbours often merge.
+    fn boards() -> impl Strategy<Value = (u8, u8, Vec<u8>)> {
+        (2..=6u8, 2..=6u8).prop_flat_map(|(width, height)| {
+            let cells = prop::collection::vec(0..=5u8, width as usize * height as usize);
+
+            (Just(width), Just(height), cells)
+        })
+    }
+
+    fn board_of(width: u8, height: u8, exponents: &[u8]) -> GameBoard {
+        let mut game_board = GameBoard::new(width, height);
+
+        for (index, exponent) in exponents.iter().copied().enumerate() {
+            let (x, y) = ((index % width as usize) as u8, (index / width as usize) as u8);
+
+            game_board.set_cell(x, y, BlockSize::try_from_exponent(exponent));
+        }
+
+        game_board
+    }
+
+    /// Every block as `(x, y, tile)`, row by row.
+    fn tiles(game_board: &GameBoard) -> Vec<(u8, u8, Tile)> {
+        (0..game_board.height())
+            .flat_map(|y| (0..game_board.width()).map(move |x| (x, y)))
+            .filter_map(|(x, y)| game_board.get_tile(x, y).map(|tile| (x, y, tile)))
+            .collect()
+    }
+
+    fn block_sum(game_board: &GameBoard) -> u64 {
+        tiles(game_board).iter().map(|(_, _, tile)| u64::from(tile.block_size.value())).sum()
+    }
+
+    proptest! {
+        #[test]
+        fn moves_keep_block_sum((width, height, exponents) in boards()) {
+            let game_board = board_of(width, height, &exponents);
+
+            for direction in GameMovementDirection::ALL.iter().copied() {
+                let mut moved = game_board.clone();
+
+                moved.apply_move(direction);
+
+                prop_assert_eq!(block_sum(&moved), block_sum(&game_board));
+            }
+        }
+
+        #[test]
+        fn mirrored_moves((width, height, exponents) in boards()) {
+            let game_board = board_of(width, height, &exponents);
+
+            // flipped from left to right, then from top to bottom
+            for symmetry in [Symmetry::reflection(0), Symmetry::reflection(2)].iter().copied() {
+                for direction in GameMovementDirection::ALL.iter().copied() {
+                    let mut moved = game_board.clone();
+                    let mut mirrored_moved = game_board.transformed(symmetry);
+
+                    let outcome = moved.apply_move(direction);
+                    let mirrored_outcome = mirrored_moved.apply_move(symmetry.direction(direction));
+
+                    prop_assert_eq!(tiles(&mirrored_moved), tiles(&moved.transformed(symmetry)), "{:?}", direction);
+                    prop_assert_eq!(mirrored_outcome.points, outcome.points);
+                }
+            }
+        }
+
+        #[test]
+        fn rotated_moves((width, height, exponents) in boards()) {
+            let game_board = board_of(width, height, &exponents);
+
+            for direction in GameMovementDirection::ALL.iter().copied() {
+                let mut moved = game_board.clone();
+                let mut rotated_moved = game_board.rotate();
+
+                let outcome = moved.apply_move(direction);
+                let rotated_outcome = rotated_moved.apply_move(Symmetry::rotation(1).direction(direction));
+
+                prop_assert_eq!(tiles(&rotated_moved), tiles(&moved.rotate()), "{:?}", direction);
+                prop_assert_eq!(rotated_outcome.points, outcome.points);
+            }
+        }
+
+        #[test]
+        fn no_op_moves_change_nothing((width, height, exponents) in boards()) {
+            let game_board = board_of(width, height, &exponents);
+
+            for direction in GameMovementDirection::ALL.iter().copied() {
+                let mut moved = game_board.clone();
+                let outcome = moved.apply_move(direction);
+
+                prop_assert_eq!(outcome.changed(), game_board.legal_moves().contains(&direction));
+
+                if !outcome.changed() {
+                    prop_assert_eq!(&outcome, &MoveOutcome::new(direction));
+                    prop_assert_eq!(tiles(&moved), tiles(&game_board));
+                    prop_assert_eq!((moved.score(), moved.moves()), (game_board.score(), game_board.moves()));
+                    prop_assert_eq!(moved.next_tile_id(), game_board.next_tile_id());
+                }
+            }
+        }
+
+        #[test]
+        fn steps_match_motions((width, height, exponents) in boards()) {
+            let game_board = board_of(width, height, &exponents);
+
+            for direction in GameMovementDirection::ALL.iter().copied() {
+                let outcome = game_board.clone().apply_move(direction);
+
+                for (x, y, tile) in tiles(&game_board) {
+                    let distance = match outcome.motions.iter().find(|motion| motion.id == tile.id) {
+                        Some(motion) => {
+                            prop_assert_eq!(motion.from, (x, y));
+
+                            let (from_x, from_y) = (i16::from(motion.from.0), i16::from(motion.from.1));
+                            let (to_x, to_y) = (i16::from(motion.to.0), i16::from(motion.to.1));
+
+                            ((to_x - from_x).abs() + (to_y - from_y).abs()) as u8
+                        },
+                        None => 0,
+                    };
+
+                    prop_assert_eq!(game_board.steps(x, y, direction), distance, "{:?} at {}, {}", direction, x, y);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! The rules of 2048 without a renderer: the board, how blocks move and
+//! merge, and how new blocks spawn.
+
+mod bitboard;
+mod block;
+mod direction;
+mod game_board;
+mod move_outcome;
+pub mod palette;
+mod solver;
+mod spawn_policy;
+mod strategy;
+mod symmetry;
+mod tile;
+
+pub use bitboard::*;
+pub use block::*;
+pub use direction::*;
+pub use game_board::*;
+pub use move_outcome::*;
+pub use solver::*;
+pub use spawn_policy::*;
+pub use strategy::*;
+pub use symmetry::*;
+pub use tile::*;
@@ -0,0 +1,55 @@
+use crate::block::BlockSize;
+use crate::direction::GameMovementDirection;
+use crate::tile::TileId;
+
+/// A block sliding from one cell to another.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TileMotion {
+    pub id: TileId,
+    pub from: (u8, u8),
+    pub to: (u8, u8),
+}
+
+/// Two blocks becoming one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TileMerge {
+    /// The block that was in the way. It keeps its ID and grows.
+    pub id: TileId,
+    /// The block that arrived and is gone after the move.
+    pub merged_id: TileId,
+    /// Where the two blocks were before the move. One of them is `to` when
+    /// that block did not move.
+    pub sources: [(u8, u8); 2],
+    pub to: (u8, u8),
+    /// The block the merge produced.
+    pub block_size: BlockSize,
+}
+
+/// Everything a move did to the board, enough to animate it without
+/// looking at the board again.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MoveOutcome {
+    pub direction: GameMovementDirection,
+    /// Blocks that moved, including the ones that merged. Blocks that
+    /// stayed in place are not listed.
+    pub motions: Vec<TileMotion>,
+    pub merges: Vec<TileMerge>,
+    /// The sum of the values of the blocks the merges produced.
+    pub points: u32,
+}
+
+impl MoveOutcome {
+    pub fn new(direction: GameMovementDirection) -> Self {
+        MoveOutcome {
+            direction,
+            motions: Vec::new(),
+            merges: Vec::new(),
+            points: 0,
+        }
+    }
+
+    /// Whether any block moved. A move that changes nothing is not made.
+    pub fn changed(&self) -> bool {
+        !self.motions.is_empty()
+    }
+}
@@ -0,0 +1,59 @@
+use crate::block::BlockSize;
+
+/// A colour as red, green and blue components.
+pub type Rgb = (u8, u8, u8);
+
+pub const BOARD_COLOR: Rgb = (187, 173, 160);
+
+/// The translucent empty cell of the classic game, blended over the board.
+pub const EMPTY_CELL_COLOR: Rgb = (205, 193, 180);
+
+pub const TEXT_PRIMARY_COLOR: Rgb = (119, 110, 101);
+pub const TEXT_INVERTED_COLOR: Rgb = (255, 255, 255);
+
+/// The classic tile colours, one per block up to 2048.
+pub fn block_color(block_size: BlockSize) -> Rgb {
+    match block_size.exponent() {
+        1 => (238, 228, 218),
+        2 => (237, 224, 200),
+        3 => (242, 177, 121),
+        4 => (245, 149, 99),
+        5 => (246, 124, 95),
+        6 => (246, 94, 59),
+        7 => (237, 207, 114),
+        8 => (237, 204, 97),
+        9 => (237, 200, 80),
+        10 => (237, 197, 63),
+        11 => (237, 194, 46),
+        // past 2048 blocks use the classic dark block, a shade darker per step
+        exponent => {
+            let shade = exponent.saturating_sub(12).saturating_mul(4);
+
+            (60u8.saturating_sub(shade), 58u8.saturating_sub(shade), 50u8.saturating_sub(shade))
+        },
+    }
+}
+
+/// Dark text on the pale tiles, the 2, 4, 128 and 256, light text on the others.
+pub fn block_text_color(block_size: BlockSize) -> Rgb {
+    match block_size.exponent() {
+        1 | 2 | 7 | 8 => TEXT_PRIMARY_COLOR,
+        _ => TEXT_INVERTED_COLOR,
+    }
+}
+
+#[cfg(test)]
+mod palette_tests {
+    use super::*;
+
+    #[test]
+    fn text_colors() {
+        let primary: Vec<u32> = (1..=BlockSize::MAX_EXPONENT)
+            .map(BlockSize::from_exponent)
+            .filter(|block_size| block_text_color(*block_size) == TEXT_PRIMARY_COLOR)
+            .map(BlockSize::value)
+            .collect();
+
+        assert_eq!(primary, vec![2, 4, 128, 256]);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::direction::GameMovementDirection;
+use crate::game_board::GameBoard;
+use crate::spawn_policy::SpawnPolicy;
+
+/// Value of a board with no moves left, below anything the heuristics give.
+const LOST_GAME_VALUE: f32 = -1.0e6;
+
+/// Spawn outcomes less likely than this are not searched any deeper.
+const MIN_PROBABILITY: f32 = 1.0e-4;
+
+/// Weights of the board features the solver tries to keep high.
+/// Features are measured on block exponents, so a 4 next to an 8 counts
+/// the same as a 1024 next to a 2048.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Heuristics {
+    /// Rows and columns that only grow or only shrink.
+    pub monotonicity: f32,
+    /// Small differences between neighbouring blocks.
+    pub smoothness: f32,
+    pub empty_cells: f32,
+    /// The largest block sitting in a corner.
+    pub max_corner: f32,
+}
+
+impl Default for Heuristics {
+    fn default() -> Self {
+        Heuristics {
+            monotonicity: 1.0,
+            smoothness: 0.1,
+            empty_cells: 2.7,
+            max_corner: 1.0,
+        }
+    }
+}
+
+impl Heuristics {
+    pub fn evaluate(&self, game_board: &GameBoard) -> f32 {
+        let exponents = exponents(game_board);
+
+        self.monotonicity * monotonicity(&exponents)
+            + self.smoothness * smoothness(&exponents)
+            + self.empty_cells * game_board.available_cells().len() as f32
+            + self.max_corner * max_corner(&exponents)
+    }
+}
+
+/// Block exponents row by row, `0` for an empty cell.
+fn exponents(game_board: &GameBoard) -> Vec<Vec<f32>> {
+    (0..game_board.height())
+        .map(|y| {
+            (0..game_board.width())
+                .map(|x| game_board.get_cell(x, y).map_or(0.0, |block_size| block_size.exponent() as f32))
+                .collect()
+        })
+        .collect()
+}
+
+fn columns(exponents: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    (0..exponents[0].len())
+        .map(|x| exponents.iter().map(|row| row[x]).collect())
+        .collect()
+}
+
+/// Minus the smaller of the rises and the falls along every row and column.
+fn monotonicity(exponents: &[Vec<f32>]) -> f32 {
+    exponents.iter()
+        .chain(columns(exponents).iter())
+        .map(|line| {
+            let (mut rises, mut falls) = (0.0, 0.0);
+
+            for pair in line.windows(2) {
+                if pair[0] < pair[1] {
+                    rises += pair[1] - pair[0];
+                } else {
+                    falls += pair[0] - pair[1];
+                }
+            }
+
+            -f32::min(rises, falls)
+        })
+        .sum()
+}
+
+/// Minus the differences between neighbouring blocks, empty cells aside.
+fn smoothness(exponents: &[Vec<f32>]) -> f32 {
+    exponents.iter()
+        .chain(columns(exponents).iter())
+        .map(|line| {
+            let blocks: Vec<f32> = line.iter().copied().filter(|exponent| *exponent > 0.0).collect();
+
+            -blocks.windows(2).map(|pair| (pair[0] - pair[1]).abs()).sum::<f32>()
+        })
+        .sum()
+}
+
+/// The exponent of the largest block when one of them is in a corner.
+fn max_corner(exponents: &[Vec<f32>]) -> f32 {
+    let max = exponents.iter().flatten().copied().fold(0.0, f32::max);
+    let (last_row, last_col) = (exponents.len() - 1, exponents[0].len() - 1);
+
+    let in_corner = [(0, 0), (0, last_col), (last_row, 0), (last_row, last_col)]
+        .iter()
+        .any(|(y, x)| exponents[*y][*x] == max);
+
+    if in_corner { max } else { 0.0 }
+}
+
+/// Expectimax search: the player picks the move with the best expected
+/// value, spawns are chance nodes weighted by the spawn policy. Each move
+/// is followed by a single spawn, whatever `blocks_per_move` says.
+#[derive(Clone, Debug)]
+pub struct Solver {
+    /// Moves to look ahead, each with the spawn after it.
+    pub depth: u8,
+    pub heuristics: Heuristics,
+}
+
+impl Default for Solver {
+    fn default() -> Self {
+        Solver {
+            depth: 2,
+            heuristics: Heuristics::default(),
+        }
+    }
+}
+
+impl Solver {
+    /// Expected value of every legal move, in `GameMovementDirection::ALL` order.
+    pub fn evaluate(&self, game_board: &GameBoard, spawn_policy: &SpawnPolicy) -> Vec<(GameMovementDirection, f32)> {
+        game_board.legal_moves()
+            .into_iter()
+            .map(|direction| {
+                let mut moved = game_board.clone();
+
+                moved.apply_move(direction);
+
+                (direction, self.chance_node(&moved, spawn_policy, self.depth.max(1), 1.0))
+            })
+            .collect()
+    }
+
+    /// The move with the best expected value and that value,
+    /// `None` when the game is over.
+    pub fn best_move(&self, game_board: &GameBoard, spawn_policy: &SpawnPolicy) -> Option<(GameMovementDirection, f32)> {
+        self.evaluate(game_board, spawn_policy)
+            .into_iter()
+            .fold(None, |best, (direction, value)| match best {
+                Some((_, best_value)) if best_value >= value => best,
+                _ => Some((direction, value)),
+            })
+    }
+
+    fn max_node(&self, game_board: &GameBoard, spawn_policy: &SpawnPolicy, depth: u8, probability: f32) -> f32 {
+        game_board.legal_moves()
+            .into_iter()
+            .map(|direction| {
+                let mut moved = game_board.clone();
+
+                moved.apply_move(direction);
+
+                self.chance_node(&moved, spawn_policy, depth, probability)
+            })
+            .fold(LOST_GAME_VALUE, f32::max)
+    }
+
+    fn chance_node(&self, game_board: &GameBoard, spawn_policy: &SpawnPolicy, depth: u8, probability: f32) -> f32 {
+        let cells = game_board.available_cells();
+
+        if cells.is_empty() {
+            return self.heuristics.evaluate(game_board);
+        }
+
+        let total_weight = spawn_policy.total_weight();
+        let mut spawned = game_board.clone();
+        let mut value = 0.0;
+
+        for (x, y) in cells.iter().copied() {
+            for (block_size, weight) in spawn_policy.distribution.iter() {
+                if *weight == 0 {
+                    continue;
+                }
+
+                let spawn_probability = *weight as f32 / total_weight as f32 / cells.len() as f32;
+
+                spawned.set_cell(x, y, Some(*block_size));
+
+                let spawned_value = if depth <= 1 || probability * spawn_probability < MIN_PROBABILITY {
+                    self.heuristics.evaluate(&spawned)
+                } else {
+                    self.max_node(&spawned, spawn_policy, depth - 1, probability * spawn_probability)
+                };
+
+                value += spawn_probability * spawned_value;
+            }
+
+            spawned.set_cell(x, y, None);
+        }
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod solver_tests {
+    use super::*;
+    use crate::block::{BlockSize, WIN_BLOCK_SIZE};
+
+    fn board_from(rows: &[[u32; 4]; 4]) -> GameBoard {
+        let mut game_board = GameBoard::with_seed(4, 4, 0);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, value) in row.iter().enumerate() {
+                game_board.set_cell(x as u8, y as u8, BlockSize::from_value(*value));
+            }
+        }
+
+        game_board
+    }
+
+    #[test]
+    fn heuristics() {
+        let heuristics = Heuristics::default();
+
+        let ordered = board_from(&[
+            [64, 32, 16, 8],
+            [32, 16, 8, 4],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+        ]);
+        let scrambled = board_from(&[
+            [8, 64, 4, 16],
+            [16, 4, 32, 8],
+            [0, 0, 0, 0],
+            [0, 0, 0, 32],
+        ]);
+
+        assert!(heuristics.evaluate(&ordered) > heuristics.evaluate(&scrambled));
+
+        let exponents = exponents(&ordered);
+
+        assert_eq!(monotonicity(&exponents), 0.0);
+        assert_eq!(smoothness(&exponents), -10.0);
+        assert_eq!(max_corner(&exponents), 6.0);
+    }
+
+    #[test]
+    fn legal_moves_only() {
+        let solver = Solver::default();
+        let spawn_policy = SpawnPolicy::default();
+
+        let stuck = board_from(&[
+            [2, 4, 2, 4],
+            [4, 2, 4, 2],
+            [2, 4, 2, 4],
+            [4, 2, 4, 2],
+        ]);
+
+        assert!(solver.evaluate(&stuck, &spawn_policy).is_empty());
+        assert_eq!(solver.best_move(&stuck, &spawn_policy), None);
+
+        // the only way to make room is to merge the two 2 blocks sideways
+        let one_merge = board_from(&[
+            [2, 2, 4, 8],
+            [4, 8, 16, 32],
+            [8, 16, 32, 64],
+            [16, 32, 64, 128],
+        ]);
+
+        let evaluation = solver.evaluate(&one_merge, &spawn_policy);
+
+        assert_eq!(
+            evaluation.iter().map(|(direction, _)| *direction).collect::<Vec<_>>(),
+            vec![GameMovementDirection::Right, GameMovementDirection::Left],
+        );
+        assert!(evaluation.iter().all(|(_, value)| *value > LOST_GAME_VALUE));
+    }
+
+    #[test]
+    fn large_weights() {
+        let solver = Solver::default();
+        let spawn_policy = SpawnPolicy {
+            distribution: vec![
+                (BlockSize::from_exponent(1), u32::MAX),
+                (BlockSize::from_exponent(2), u32::MAX),
+            ],
+            ..Default::default()
+        };
+        let one_merge = board_from(&[
+            [2, 2, 4, 8],
+            [4, 8, 16, 32],
+            [8, 16, 32, 64],
+            [16, 32, 64, 128],
+        ]);
+
+        // the weights add up past u32::MAX but still give even chances
+        let evaluation = solver.evaluate(&one_merge, &spawn_policy);
+
+        assert_eq!(evaluation.len(), 2);
+        assert!(evaluation.iter().all(|(_, value)| *value > LOST_GAME_VALUE));
+    }
+
+    #[test]
+    fn plays_well() {
+        let solver = Solver {
+            depth: 1,
+            ..Default::default()
+        };
+        let spawn_policy = SpawnPolicy::default();
+        let mut game_board = GameBoard::with_seed(4, 4, 7);
+
+        game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        while let Some((direction, _)) = solver.best_move(&game_board, &spawn_policy) {
+            game_board.apply_move(direction);
+            game_board.spawn(&spawn_policy, spawn_policy.blocks_per_move);
+
+            if game_board.max_block() >= Some(WIN_BLOCK_SIZE) {
+                break;
+            }
+        }
+
+        assert!(game_board.max_block() >= BlockSize::from_value(512), "{}", game_board.pretty_string());
+    }
+}
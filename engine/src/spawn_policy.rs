@@ -0,0 +1,156 @@
+use rand::Rng;
+
+use crate::block::BlockSize;
+
+/// Decides which blocks appear on the board: the values they get, how many
+/// appear after each move and how many a new game starts with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpawnPolicy {
+    /// Block values paired with their relative weights.
+    pub distribution: Vec<(BlockSize, u32)>,
+    pub blocks_per_move: u8,
+    pub initial_blocks: u8,
+}
+
+impl Default for SpawnPolicy {
+    /// The classic rules: a 2 nine times out of ten, otherwise a 4,
+    /// one block per move and two blocks to start with.
+    fn default() -> Self {
+        SpawnPolicy {
+            distribution: vec![
+                (BlockSize::from_exponent(1), 9),
+                (BlockSize::from_exponent(2), 1),
+            ],
+            blocks_per_move: 1,
+            initial_blocks: 2,
+        }
+    }
+}
+
+impl SpawnPolicy {
+    /// A policy read from outside the program, from the command line or a
+    /// file: some weight has to be above zero and both counts positive.
+    pub fn new(distribution: Vec<(BlockSize, u32)>, blocks_per_move: u8, initial_blocks: u8) -> Result<Self, String> {
+        let spawn_policy = SpawnPolicy {
+            distribution,
+            blocks_per_move,
+            initial_blocks,
+        };
+
+        if spawn_policy.distribution.is_empty() {
+            return Err("invalid spawn distribution: no blocks to spawn".to_string());
+        }
+
+        if spawn_policy.total_weight() == 0 {
+            return Err("invalid spawn distribution: the weights add up to zero".to_string());
+        }
+
+        if blocks_per_move == 0 {
+            return Err("invalid spawn policy: blocks per move must be positive".to_string());
+        }
+
+        if initial_blocks == 0 {
+            return Err("invalid spawn policy: initial blocks must be positive".to_string());
+        }
+
+        Ok(spawn_policy)
+    }
+
+    /// The sum of the weights, wider than them so that weights near
+    /// `u32::MAX` cannot overflow it.
+    pub fn total_weight(&self) -> u64 {
+        self.distribution.iter().map(|(_, weight)| u64::from(*weight)).sum()
+    }
+
+    pub fn pick_block<R: Rng>(&self, rng: &mut R) -> BlockSize {
+        let total_weight = self.total_weight();
+
+        if total_weight == 0 {
+            return BlockSize::MIN;
+        }
+
+        let mut roll = rng.gen_range(0..total_weight);
+
+        for (block_size, weight) in self.distribution.iter() {
+            let weight = u64::from(*weight);
+
+            if roll < weight {
+                return *block_size;
+            }
+
+            roll -= weight;
+        }
+
+        unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod spawn_policy_tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::game_board::GameRng;
+
+    #[test]
+    fn validation() {
+        let default = SpawnPolicy::default();
+
+        assert_eq!(SpawnPolicy::new(default.distribution.clone(), 1, 2), Ok(default));
+        assert!(SpawnPolicy::new(vec![(BlockSize::MIN, 0), (BlockSize::from_exponent(2), 1)], 1, 1).is_ok());
+        assert!(SpawnPolicy::new(vec![(BlockSize::MIN, u32::MAX), (BlockSize::from_exponent(2), 1)], 1, 1).is_ok());
+
+        assert!(SpawnPolicy::new(Vec::new(), 1, 2).is_err());
+        assert!(SpawnPolicy::new(vec![(BlockSize::MIN, 0)], 1, 2).is_err());
+        assert!(SpawnPolicy::new(vec![(BlockSize::MIN, 1)], 0, 2).is_err());
+        assert!(SpawnPolicy::new(vec![(BlockSize::MIN, 1)], 1, 0).is_err());
+    }
+
+    #[test]
+    fn classic_distribution() {
+        let spawn_policy = SpawnPolicy::default();
+        let mut rng = GameRng::seed_from_u64(1);
+
+        let fours = (0..10000)
+            .filter(|_| spawn_policy.pick_block(&mut rng) == BlockSize::from_exponent(2))
+            .count();
+
+        assert!((800..1200).contains(&fours), "picked {} fours", fours);
+    }
+
+    #[test]
+    fn custom_distribution() {
+        let spawn_policy = SpawnPolicy {
+            distribution: vec![
+                (BlockSize::from_exponent(1), 0),
+                (BlockSize::from_exponent(3), 1),
+            ],
+            ..Default::default()
+        };
+        let mut rng = GameRng::seed_from_u64(1);
+
+        for _ in 0..100 {
+            assert_eq!(spawn_policy.pick_block(&mut rng), BlockSize::from_exponent(3));
+        }
+    }
+
+    #[test]
+    fn large_weights() {
+        let spawn_policy = SpawnPolicy {
+            distribution: vec![
+                (BlockSize::from_exponent(1), u32::MAX),
+                (BlockSize::from_exponent(2), u32::MAX),
+            ],
+            ..Default::default()
+        };
+        let mut rng = GameRng::seed_from_u64(1);
+
+        assert_eq!(spawn_policy.total_weight(), 2 * u64::from(u32::MAX));
+
+        let fours = (0..1000)
+            .filter(|_| spawn_policy.pick_block(&mut rng) == BlockSize::from_exponent(2))
+            .count();
+
+        assert!((400..600).contains(&fours), "picked {} fours", fours);
+    }
+}
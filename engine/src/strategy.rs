@@ -0,0 +1,144 @@
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::direction::GameMovementDirection;
+use crate::game_board::{GameBoard, GameRng};
+use crate::solver::Solver;
+use crate::spawn_policy::SpawnPolicy;
+
+/// Moves tried in turn by `Strategy::Corner`, which keeps the largest
+/// blocks in the bottom left corner.
+const CORNER_PREFERENCE: [GameMovementDirection; 4] = [
+    GameMovementDirection::Down,
+    GameMovementDirection::Left,
+    GameMovementDirection::Right,
+    GameMovementDirection::Up,
+];
+
+/// A way of picking moves without a player, for self-play.
+#[derive(Clone, Debug)]
+pub enum Strategy {
+    /// Any legal move.
+    Random,
+    /// The move earning the most points now, the one leaving the most
+    /// empty cells on a tie.
+    Greedy,
+    /// Down, else left, else right, else up.
+    Corner,
+    Expectimax(Solver),
+}
+
+impl Strategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Random => "random",
+            Strategy::Greedy => "greedy",
+            Strategy::Corner => "corner",
+            Strategy::Expectimax(_) => "expectimax",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let strategy = match name {
+            "random" => Strategy::Random,
+            "greedy" => Strategy::Greedy,
+            "corner" => Strategy::Corner,
+            "expectimax" => Strategy::Expectimax(Solver::default()),
+            _ => return None,
+        };
+
+        Some(strategy)
+    }
+
+    /// The move to make, `None` when the game is over.
+    pub fn choose(&self, game_board: &GameBoard, spawn_policy: &SpawnPolicy, rng: &mut GameRng) -> Option<GameMovementDirection> {
+        match self {
+            Strategy::Random => game_board.legal_moves().choose(rng).copied(),
+            Strategy::Greedy => game_board.legal_moves()
+                .into_iter()
+                .map(|direction| {
+                    let mut moved = game_board.clone();
+                    let points = moved.apply_move(direction).points;
+
+                    (direction, (points, moved.available_cells().len()))
+                })
+                .fold(None, |best, (direction, value)| match best {
+                    Some((_, best_value)) if best_value >= value => best,
+                    _ => Some((direction, value)),
+                })
+                .map(|(direction, _)| direction),
+            Strategy::Corner => {
+                let legal_moves = game_board.legal_moves();
+
+                CORNER_PREFERENCE.iter().copied().find(|direction| legal_moves.contains(direction))
+            },
+            Strategy::Expectimax(solver) => solver.best_move(game_board, spawn_policy).map(|(direction, _)| direction),
+        }
+    }
+
+    /// Plays a game on an empty board until no move is left. The moves are
+    /// as repeatable as the spawns: random choices come from the board seed.
+    pub fn play(&self, game_board: &mut GameBoard, spawn_policy: &SpawnPolicy) {
+        let mut rng = GameRng::seed_from_u64(game_board.seed());
+
+        // a stream of its own, so the choices do not follow the spawns
+        rng.set_stream(1);
+
+        game_board.spawn(spawn_policy, spawn_policy.initial_blocks);
+
+        while let Some(direction) = self.choose(game_board, spawn_policy, &mut rng) {
+            game_board.apply_move(direction);
+            game_board.spawn(spawn_policy, spawn_policy.blocks_per_move);
+        }
+    }
+}
+
+#[cfg(test)]
+mod strategy_tests {
+    use super::*;
+    use crate::block::BlockSize;
+
+    #[test]
+    fn names() {
+        for name in ["random", "greedy", "corner", "expectimax"].iter() {
+            assert_eq!(Strategy::from_name(name).unwrap().name(), *name);
+        }
+
+        assert!(Strategy::from_name("perfect").is_none());
+    }
+
+    #[test]
+    fn games_repeat() {
+        let spawn_policy = SpawnPolicy::default();
+
+        for strategy in [Strategy::Random, Strategy::Greedy, Strategy::Corner].iter() {
+            let mut first = GameBoard::with_seed(4, 4, 5);
+            let mut second = GameBoard::with_seed(4, 4, 5);
+
+            strategy.play(&mut first, &spawn_policy);
+            strategy.play(&mut second, &spawn_policy);
+
+            assert!(first.is_over());
+            assert!(first.moves() > 0);
+            assert_eq!(first.pretty_string(), second.pretty_string(), "{}", strategy.name());
+            assert_eq!(first.score(), second.score());
+        }
+    }
+
+    #[test]
+    fn greedy_takes_points() {
+        let spawn_policy = SpawnPolicy::default();
+        let mut rng = GameRng::seed_from_u64(0);
+        let mut game_board = GameBoard::with_seed(4, 4, 0);
+
+        // only a vertical move merges the two 8 blocks
+        game_board.set_cell(0, 0, BlockSize::from_value(8));
+        game_board.set_cell(0, 1, BlockSize::from_value(8));
+        game_board.set_cell(3, 3, BlockSize::from_value(2));
+
+        let direction = Strategy::Greedy.choose(&game_board, &spawn_policy, &mut rng).unwrap();
+
+        assert!(direction == GameMovementDirection::Up || direction == GameMovementDirection::Down);
+        assert_eq!(Strategy::Corner.choose(&game_board, &spawn_policy, &mut rng), Some(GameMovementDirection::Down));
+    }
+}
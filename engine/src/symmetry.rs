@@ -0,0 +1,349 @@
+use crate::direction::GameMovementDirection;
+use crate::game_board::GameBoard;
+use crate::move_outcome::MoveOutcome;
+
+/// One of the eight ways to turn and flip a board: an optional mirror from
+/// left to right, then a number of quarter turns clockwise. A move commutes
+/// with a symmetry once its direction is turned the same way.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Symmetry {
+    pub mirrored: bool,
+    /// From 0 to 3.
+    pub quarter_turns: u8,
+}
+
+impl Symmetry {
+    pub const IDENTITY: Symmetry = Symmetry::rotation(0);
+
+    pub const ALL: [Symmetry; 8] = [
+        Symmetry::rotation(0),
+        Symmetry::rotation(1),
+        Symmetry::rotation(2),
+        Symmetry::rotation(3),
+        Symmetry::reflection(0),
+        Symmetry::reflection(1),
+        Symmetry::reflection(2),
+        Symmetry::reflection(3),
+    ];
+
+    pub const fn rotation(quarter_turns: u8) -> Self {
+        Symmetry {
+            mirrored: false,
+            quarter_turns: quarter_turns % 4,
+        }
+    }
+
+    pub const fn reflection(quarter_turns: u8) -> Self {
+        Symmetry {
+            mirrored: true,
+            quarter_turns: quarter_turns % 4,
+        }
+    }
+
+    /// The rotation that turns `direction` into a move to the left.
+    pub fn to_left(direction: GameMovementDirection) -> Self {
+        // left comes last in the clockwise order
+        Symmetry::rotation(7 - direction_index(direction))
+    }
+
+    /// The symmetry undoing this one. Every reflection undoes itself.
+    pub fn inverse(self) -> Self {
+        if self.mirrored {
+            self
+        } else {
+            Symmetry::rotation(4 - self.quarter_turns)
+        }
+    }
+
+    /// The size of a `width` by `height` board once transformed.
+    pub fn size(self, width: u8, height: u8) -> (u8, u8) {
+        if self.quarter_turns % 2 == 1 {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Where the cell `x, y` of a `width` by `height` board goes.
+    pub fn cell(self, x: u8, y: u8, width: u8, height: u8) -> (u8, u8) {
+        let (mut x, mut y, mut width, mut height) = if self.mirrored {
+            (width - 1 - x, y, width, height)
+        } else {
+            (x, y, width, height)
+        };
+
+        for _ in 0..self.quarter_turns {
+            let turned = (height - 1 - y, x, height, width);
+
+            x = turned.0;
+            y = turned.1;
+            width = turned.2;
+            height = turned.3;
+        }
+
+        (x, y)
+    }
+
+    pub fn direction(self, direction: GameMovementDirection) -> GameMovementDirection {
+        let direction = match direction {
+            GameMovementDirection::Left if self.mirrored => GameMovementDirection::Right,
+            GameMovementDirection::Right if self.mirrored => GameMovementDirection::Left,
+            direction => direction,
+        };
+
+        GameMovementDirection::ALL[((direction_index(direction) + self.quarter_turns) % 4) as usize]
+    }
+}
+
+/// The position of `direction` in `GameMovementDirection::ALL`, which lists
+/// the directions clockwise.
+fn direction_index(direction: GameMovementDirection) -> u8 {
+    GameMovementDirection::ALL.iter().position(|other| *other == direction).unwrap() as u8
+}
+
+/// The size and blocks of a board, without tile IDs, scores or spawns:
+/// two boards with equal keys play out the same.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardKey {
+    width: u8,
+    height: u8,
+    /// Exponents row by row, 0 for an empty cell.
+    cells: Vec<u8>,
+}
+
+impl GameBoard {
+    pub fn transformed(&self, symmetry: Symmetry) -> GameBoard {
+        let (width, height) = (self.width(), self.height());
+        let (new_width, new_height) = symmetry.size(width, height);
+
+        self.remapped(new_width, new_height, |x, y| symmetry.cell(x, y, width, height))
+    }
+
+    /// The board turned a quarter clockwise. Its width is the old height.
+    pub fn rotate(&self) -> GameBoard {
+        self.transformed(Symmetry::rotation(1))
+    }
+
+    /// The board flipped over its top left to bottom right diagonal.
+    pub fn transpose(&self) -> GameBoard {
+        self.transformed(Symmetry::reflection(3))
+    }
+
+    /// The board flipped from left to right.
+    pub fn mirror(&self) -> GameBoard {
+        self.transformed(Symmetry::reflection(0))
+    }
+
+    pub fn key(&self) -> BoardKey {
+        let cells = (0..self.height())
+            .flat_map(|y| (0..self.width()).map(move |x| (x, y)))
+            .map(|(x, y)| self.get_cell(x, y).map_or(0, |block_size| block_size.exponent()))
+            .collect();
+
+        BoardKey {
+            width: self.width(),
+            height: self.height(),
+            cells,
+        }
+    }
+
+    /// A key equal for all the rotations and reflections of a board.
+    pub fn canonical_key(&self) -> BoardKey {
+        self.canonical_form().0.key()
+    }
+
+    /// The rotation or reflection of the board with the smallest key, and
+    /// the symmetry that gives it. A move found on the canonical board is
+    /// played here in `symmetry.inverse().direction(..)`.
+    pub fn canonical_form(&self) -> (GameBoard, Symmetry) {
+        Symmetry::ALL.iter()
+            .map(|symmetry| (self.transformed(*symmetry), *symmetry))
+            .min_by_key(|(game_board, _)| game_board.key())
+            .expect("there is always a symmetry")
+    }
+
+    /// Makes a move as "rotate, move left, rotate back". The board and the
+    /// outcome are those of `apply_move`, though the motions and merges may
+    /// be listed in another order.
+    pub fn apply_move_by_rotation(&mut self, direction: GameMovementDirection) -> MoveOutcome {
+        let symmetry = Symmetry::to_left(direction);
+        let mut turned = self.transformed(symmetry);
+        let outcome = turned.apply_move(GameMovementDirection::Left);
+
+        let back = symmetry.inverse();
+        let (width, height) = (turned.width(), turned.height());
+        let cell = |(x, y): (u8, u8)| back.cell(x, y, width, height);
+
+        *self = turned.transformed(back);
+
+        let mut result = MoveOutcome::new(direction);
+
+        result.points = outcome.points;
+        result.motions = outcome.motions.into_iter()
+            .map(|mut motion| {
+                motion.from = cell(motion.from);
+                motion.to = cell(motion.to);
+                motion
+            })
+            .collect();
+        result.merges = outcome.merges.into_iter()
+            .map(|mut merge| {
+                merge.sources = [cell(merge.sources[0]), cell(merge.sources[1])];
+                merge.to = cell(merge.to);
+                merge
+            })
+            .collect();
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod symmetry_tests {
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::block::BlockSize;
+    use crate::game_board::GameRng;
+
+    fn random_board(rng: &mut GameRng, width: u8, height: u8) -> GameBoard {
+        let mut game_board = GameBoard::new(width, height);
+
+        for y in 0..height {
+            for x in 0..width {
+                if rng.gen_bool(0.7) {
+                    game_board.set_cell(x, y, BlockSize::try_from_exponent(rng.gen_range(1..=4)));
+                }
+            }
+        }
+
+        game_board
+    }
+
+    /// Values row by row, 0 for an empty cell.
+    fn values(game_board: &GameBoard) -> Vec<Vec<u32>> {
+        (0..game_board.height())
+            .map(|y| {
+                (0..game_board.width())
+                    .map(|x| game_board.get_cell(x, y).map_or(0, |block_size| block_size.value()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn operations() {
+        let mut game_board = GameBoard::new(3, 2);
+
+        // 2 4 8
+        // 16 - 32
+        for (x, y, value) in [(0, 0, 2), (1, 0, 4), (2, 0, 8), (0, 1, 16), (2, 1, 32)].iter().copied() {
+            game_board.set_cell(x, y, BlockSize::from_value(value));
+        }
+
+        assert_eq!(values(&game_board.rotate()), vec![vec![16, 2], vec![0, 4], vec![32, 8]]);
+        assert_eq!(values(&game_board.transpose()), vec![vec![2, 16], vec![4, 0], vec![8, 32]]);
+        assert_eq!(values(&game_board.mirror()), vec![vec![8, 4, 2], vec![32, 0, 16]]);
+        assert_eq!(values(&game_board.rotate().rotate().rotate().rotate()), values(&game_board));
+        assert_eq!(game_board.rotate().get_tile(1, 0), game_board.get_tile(0, 0));
+    }
+
+    #[test]
+    fn inverses() {
+        let mut rng = GameRng::seed_from_u64(1);
+        let game_board = random_board(&mut rng, 4, 3);
+
+        for symmetry in Symmetry::ALL.iter().copied() {
+            let back = game_board.transformed(symmetry).transformed(symmetry.inverse());
+
+            assert_eq!(values(&back), values(&game_board), "{:?}", symmetry);
+
+            for direction in GameMovementDirection::ALL.iter().copied() {
+                assert_eq!(symmetry.inverse().direction(symmetry.direction(direction)), direction);
+            }
+        }
+    }
+
+    #[test]
+    fn canonical_keys() {
+        let mut rng = GameRng::seed_from_u64(2);
+
+        for _ in 0..50 {
+            let game_board = random_board(&mut rng, 4, 4);
+            let key = game_board.canonical_key();
+
+            for symmetry in Symmetry::ALL.iter().copied() {
+                assert_eq!(game_board.transformed(symmetry).canonical_key(), key);
+            }
+
+            let (canonical, symmetry) = game_board.canonical_form();
+
+            assert_eq!(canonical.key(), key);
+            assert_eq!(values(&game_board.transformed(symmetry)), values(&canonical));
+        }
+
+        let mut corner = GameBoard::new(4, 4);
+        let mut edge = GameBoard::new(4, 4);
+
+        corner.set_cell(0, 0, BlockSize::from_value(2));
+        edge.set_cell(1, 0, BlockSize::from_value(2));
+
+        assert_ne!(corner.canonical_key(), edge.canonical_key());
+        assert_eq!(corner.canonical_key(), corner.rotate().canonical_key());
+        assert_ne!(corner.key(), corner.rotate().key());
+    }
+
+    #[test]
+    fn moves_commute() {
+        let mut rng = GameRng::seed_from_u64(3);
+
+        for _ in 0..100 {
+            let game_board = random_board(&mut rng, 4, 3);
+
+            for symmetry in Symmetry::ALL.iter().copied() {
+                for direction in GameMovementDirection::ALL.iter().copied() {
+                    let mut moved = game_board.clone();
+                    let mut transformed = game_board.transformed(symmetry);
+
+                    let points = moved.apply_move(direction).points;
+
+                    assert_eq!(transformed.apply_move(symmetry.direction(direction)).points, points);
+                    assert_eq!(values(&transformed), values(&moved.transformed(symmetry)), "{:?} {:?}", symmetry, direction);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn moves_by_rotation() {
+        let mut rng = GameRng::seed_from_u64(4);
+
+        for _ in 0..100 {
+            let game_board = random_board(&mut rng, 5, 3);
+
+            for direction in GameMovementDirection::ALL.iter().copied() {
+                let mut expected = game_board.clone();
+                let mut moved = game_board.clone();
+
+                let mut expected_outcome = expected.apply_move(direction);
+                let mut outcome = moved.apply_move_by_rotation(direction);
+
+                for outcome in [&mut expected_outcome, &mut outcome].iter_mut() {
+                    outcome.motions.sort_by_key(|motion| motion.id);
+                    outcome.merges.sort_by_key(|merge| merge.id);
+                }
+
+                assert_eq!(outcome, expected_outcome);
+                assert_eq!(moved.pretty_string(), expected.pretty_string());
+                assert_eq!((moved.width(), moved.score(), moved.moves()), (5, expected.score(), expected.moves()));
+
+                for y in 0..3 {
+                    for x in 0..5 {
+                        assert_eq!(moved.get_tile(x, y), expected.get_tile(x, y));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::block::BlockSize;
+
+/// Identifies a block from the moment it spawns until another block merges
+/// into it or it merges into another one. IDs start at 1 with every game
+/// and are never reused within one.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TileId(pub u32);
+
+impl TileId {
+    pub const FIRST: TileId = TileId(1);
+
+    pub fn next(self) -> Self {
+        TileId(self.0 + 1)
+    }
+}
+
+/// A block on the board.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Tile {
+    pub id: TileId,
+    pub block_size: BlockSize,
+}
@@ -0,0 +1,149 @@
+use std::{env, process};
+
+use game_2048_cli::{parse_block_count, parse_board_size, parse_seed, parse_solver_depth, parse_spawn_distribution};
+use game_2048_engine::{GameBoard, Solver, SpawnPolicy, Strategy};
+
+mod simulation;
+
+use simulation::{Report, ReportFormat, Simulation};
+
+const USAGE: &str = "usage: game_2048_sim [--games <count>] [--strategy random|greedy|corner|expectimax] \
+    [--threads <count>] [--format table|json|csv] [--size <cols>x<rows>] [--seed <number>] \
+    [--spawn <value>:<weight>,...] [--spawn-per-move <count>] [--initial-blocks <count>] [--solver-depth <moves>]";
+
+/// Simulator options read from the command line.
+struct Options {
+    width: u8,
+    height: u8,
+    /// Seed of the first game, random when not given.
+    seed: Option<u64>,
+    spawn_policy: SpawnPolicy,
+    simulation: Simulation,
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        width: 4,
+        height: 4,
+        seed: None,
+        spawn_policy: SpawnPolicy::default(),
+        simulation: Simulation::default(),
+    };
+    let mut solver = Solver::default();
+
+    while let Some(arg) = args.next() {
+        let mut next_value = || args.next().ok_or(format!("{} requires a value", arg));
+
+        match &arg[..] {
+            "--games" => {
+                let value = next_value()?;
+
+                options.simulation.games = value.parse::<u32>()
+                    .ok()
+                    .filter(|games| *games > 0)
+                    .ok_or(format!("invalid game count: {}, expected a positive number", value))?;
+            },
+            "--strategy" => {
+                let value = next_value()?;
+
+                options.simulation.strategy = Strategy::from_name(&value)
+                    .ok_or(format!("unknown strategy: {}, expected random, greedy, corner or expectimax", value))?;
+            },
+            "--threads" => {
+                let value = next_value()?;
+
+                options.simulation.threads = value.parse::<usize>()
+                    .ok()
+                    .filter(|threads| *threads > 0)
+                    .ok_or(format!("invalid thread count: {}, expected a positive number", value))?;
+            },
+            "--format" => {
+                let value = next_value()?;
+
+                options.simulation.format = ReportFormat::from_name(&value)
+                    .ok_or(format!("unknown format: {}, expected table, json or csv", value))?;
+            },
+            "--size" => {
+                let (width, height) = parse_board_size(&next_value()?)?;
+
+                options.width = width;
+                options.height = height;
+            },
+            "--seed" => options.seed = Some(parse_seed(&next_value()?)?),
+            "--spawn" => options.spawn_policy.distribution = parse_spawn_distribution(&next_value()?)?,
+            "--spawn-per-move" => options.spawn_policy.blocks_per_move = parse_block_count(&next_value()?)?,
+            "--initial-blocks" => options.spawn_policy.initial_blocks = parse_block_count(&next_value()?)?,
+            "--solver-depth" => solver.depth = parse_solver_depth(&next_value()?)?,
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    let SpawnPolicy { distribution, blocks_per_move, initial_blocks } = options.spawn_policy;
+
+    options.spawn_policy = SpawnPolicy::new(distribution, blocks_per_move, initial_blocks)?;
+
+    // the search depth may come before or after the strategy
+    if let Strategy::Expectimax(expectimax) = &mut options.simulation.strategy {
+        *expectimax = solver;
+    }
+
+    Ok(options)
+}
+
+fn main() {
+    let options = parse_args(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+
+        process::exit(2);
+    });
+
+    // a random first seed is printed with the report, so the run can be repeated
+    let seed = options.seed.unwrap_or_else(|| GameBoard::new(options.width, options.height).seed());
+    let simulation = &options.simulation;
+    let results = simulation.run(options.width, options.height, seed, &options.spawn_policy);
+
+    print!("{}", Report::new(&simulation.strategy, seed, &results).format(simulation.format));
+}
+
+#[cfg(test)]
+mod sim_tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn arguments() {
+        let options = parse(&[]).unwrap();
+
+        assert_eq!((options.width, options.height, options.seed), (4, 4, None));
+        assert_eq!(options.simulation.games, Simulation::default().games);
+        assert_eq!(options.simulation.strategy.name(), "corner");
+        assert_eq!(options.simulation.format, ReportFormat::Table);
+
+        let options = parse(&[
+            "--solver-depth", "1", "--strategy", "expectimax", "--games", "50", "--threads", "3",
+            "--format", "csv", "--size", "5x5", "--seed", "7", "--spawn", "2:1",
+        ]).unwrap();
+        let simulation = &options.simulation;
+
+        assert_eq!((simulation.games, simulation.threads, simulation.format), (50, 3, ReportFormat::Csv));
+        assert_eq!((options.width, options.seed), (5, Some(7)));
+        assert_eq!(options.spawn_policy.distribution.len(), 1);
+
+        match &simulation.strategy {
+            Strategy::Expectimax(solver) => assert_eq!(solver.depth, 1),
+            strategy => panic!("unexpected strategy: {}", strategy.name()),
+        }
+
+        assert!(parse(&["--games"]).is_err());
+        assert!(parse(&["--games", "0"]).is_err());
+        assert!(parse(&["--strategy", "perfect"]).is_err());
+        assert!(parse(&["--format", "xml"]).is_err());
+        assert!(parse(&["--size", "9x9"]).is_err());
+        assert!(parse(&["--spawn", "2:0"]).is_err());
+        assert!(parse(&["--spawn-per-move", "0"]).is_err());
+        assert!(parse(&["simulate"]).is_err());
+    }
+}
@@ -0,0 +1,296 @@
+use std::thread;
+
+use game_2048_engine::{GameBoard, SpawnPolicy, Strategy, WIN_BLOCK_SIZE};
+use serde::Serialize;
+
+/// Scores reported as percentiles.
+const SCORE_PERCENTILES: [u32; 4] = [10, 50, 90, 99];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ReportFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+impl ReportFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "table" => Some(ReportFormat::Table),
+            "json" => Some(ReportFormat::Json),
+            "csv" => Some(ReportFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// How many games to play, how, and how to report on them.
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    pub games: u32,
+    pub strategy: Strategy,
+    pub threads: usize,
+    pub format: ReportFormat,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Simulation {
+            games: 100,
+            strategy: Strategy::Corner,
+            threads: num_cpus::get(),
+            format: ReportFormat::Table,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GameResult {
+    pub seed: u64,
+    pub score: u32,
+    pub max_block: u32,
+    pub moves: u32,
+}
+
+impl Simulation {
+    /// Plays the games seeded `seed`, `seed + 1` and so on, spread over the
+    /// threads. The results come back in seed order, so they are the same
+    /// whatever the number of threads.
+    pub fn run(&self, width: u8, height: u8, seed: u64, spawn_policy: &SpawnPolicy) -> Vec<GameResult> {
+        let threads = self.threads.max(1).min(self.games.max(1) as usize);
+
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let (strategy, spawn_policy) = (self.strategy.clone(), spawn_policy.clone());
+                let games = self.games as usize;
+
+                thread::spawn(move || {
+                    (worker..games)
+                        .step_by(threads)
+                        .map(|game| {
+                            let mut game_board = GameBoard::with_seed(width, height, seed.wrapping_add(game as u64));
+
+                            strategy.play(&mut game_board, &spawn_policy);
+
+                            GameResult {
+                                seed: game_board.seed(),
+                                score: game_board.score(),
+                                max_block: game_board.max_block().map_or(0, |block_size| block_size.value()),
+                                moves: game_board.moves(),
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let mut results: Vec<GameResult> = workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("simulated games do not panic"))
+            .collect();
+
+        results.sort_by_key(|result| result.seed.wrapping_sub(seed));
+
+        results
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct MaxBlockCount {
+    pub value: u32,
+    pub games: u32,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct ScorePercentile {
+    pub percentile: u32,
+    pub score: u32,
+}
+
+/// What a batch of games says about a strategy.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Report {
+    pub strategy: String,
+    pub games: u32,
+    /// Seed of the first game, the others follow it.
+    pub seed: u64,
+    /// Share of the games reaching the 2048 block, from 0 to 1.
+    pub win_rate: f64,
+    pub average_score: f64,
+    pub score_percentiles: Vec<ScorePercentile>,
+    pub average_moves: f64,
+    /// How many games ended with each largest block, smallest first.
+    pub max_blocks: Vec<MaxBlockCount>,
+}
+
+impl Report {
+    pub fn new(strategy: &Strategy, seed: u64, results: &[GameResult]) -> Self {
+        let games = results.len() as u32;
+        let average = |value: &dyn Fn(&GameResult) -> u32| {
+            results.iter().map(|result| f64::from(value(result))).sum::<f64>() / f64::from(games.max(1))
+        };
+
+        let mut scores: Vec<u32> = results.iter().map(|result| result.score).collect();
+        scores.sort_unstable();
+
+        // nearest rank: the smallest score at least this share of the games reach
+        let score_percentiles = SCORE_PERCENTILES.iter()
+            .map(|percentile| ScorePercentile {
+                percentile: *percentile,
+                score: match scores.len() {
+                    0 => 0,
+                    len => scores[(*percentile as usize * len).saturating_sub(1) / 100],
+                },
+            })
+            .collect();
+
+        let mut max_blocks: Vec<MaxBlockCount> = Vec::new();
+        let mut values: Vec<u32> = results.iter().map(|result| result.max_block).collect();
+        values.sort_unstable();
+
+        for value in values {
+            match max_blocks.last_mut() {
+                Some(count) if count.value == value => count.games += 1,
+                _ => max_blocks.push(MaxBlockCount { value, games: 1 }),
+            }
+        }
+
+        Report {
+            strategy: strategy.name().to_string(),
+            games,
+            seed,
+            win_rate: results.iter().filter(|result| result.max_block >= WIN_BLOCK_SIZE.value()).count() as f64
+                / f64::from(games.max(1)),
+            average_score: average(&|result| result.score),
+            score_percentiles,
+            average_moves: average(&|result| result.moves),
+            max_blocks,
+        }
+    }
+
+    pub fn format(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Table => self.to_table(),
+            ReportFormat::Json => serde_json::to_string_pretty(self).expect("reports are always serializable"),
+            ReportFormat::Csv => self.to_csv(),
+        }
+    }
+
+    fn to_table(&self) -> String {
+        let mut table = format!(
+            "strategy        {}\ngames           {}\nseed            {}\nwin rate        {:.1}%\naverage score   {:.1}\n",
+            self.strategy, self.games, self.seed, self.win_rate * 100.0, self.average_score,
+        );
+
+        for percentile in self.score_percentiles.iter() {
+            table += &format!("score p{:<8} {}\n", percentile.percentile, percentile.score);
+        }
+
+        table += &format!("average moves   {:.1}\n\nmax block     games    share\n", self.average_moves);
+
+        for count in self.max_blocks.iter() {
+            table += &format!(
+                "{:>9} {:>9} {:>7.1}%\n",
+                count.value, count.games, f64::from(count.games) * 100.0 / f64::from(self.games.max(1)),
+            );
+        }
+
+        table
+    }
+
+    /// One `statistic,value` row per figure, the largest blocks as `max_block_<value>`.
+    fn to_csv(&self) -> String {
+        let mut csv = format!(
+            "statistic,value\nstrategy,{}\ngames,{}\nseed,{}\nwin_rate,{}\naverage_score,{}\n",
+            self.strategy, self.games, self.seed, self.win_rate, self.average_score,
+        );
+
+        for percentile in self.score_percentiles.iter() {
+            csv += &format!("score_p{},{}\n", percentile.percentile, percentile.score);
+        }
+
+        csv += &format!("average_moves,{}\n", self.average_moves);
+
+        for count in self.max_blocks.iter() {
+            csv += &format!("max_block_{},{}\n", count.value, count.games);
+        }
+
+        csv
+    }
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use super::*;
+
+    fn result(score: u32, max_block: u32) -> GameResult {
+        GameResult {
+            seed: 0,
+            score,
+            max_block,
+            moves: score / 10,
+        }
+    }
+
+    #[test]
+    fn threads_do_not_change_results() {
+        let spawn_policy = SpawnPolicy::default();
+        let mut simulation = Simulation {
+            games: 12,
+            strategy: Strategy::Random,
+            threads: 1,
+            format: ReportFormat::Table,
+        };
+
+        let single = simulation.run(4, 4, 40, &spawn_policy);
+
+        simulation.threads = 5;
+
+        assert_eq!(simulation.run(4, 4, 40, &spawn_policy), single);
+        assert_eq!(single.iter().map(|result| result.seed).collect::<Vec<_>>(), (40..52).collect::<Vec<_>>());
+        assert!(single.iter().all(|result| result.moves > 0 && result.max_block >= 4));
+    }
+
+    #[test]
+    fn statistics() {
+        let results: Vec<GameResult> = (1..=10)
+            .map(|game| result(game * 1000, if game > 8 { 2048 } else { 512 }))
+            .collect();
+
+        let report = Report::new(&Strategy::Corner, 3, &results);
+
+        assert_eq!(report.games, 10);
+        assert_eq!(report.win_rate, 0.2);
+        assert_eq!(report.average_score, 5500.0);
+        assert_eq!(report.average_moves, 550.0);
+        assert_eq!(
+            report.score_percentiles.iter().map(|percentile| percentile.score).collect::<Vec<_>>(),
+            vec![1000, 5000, 9000, 10000],
+        );
+        assert_eq!(report.max_blocks, vec![
+            MaxBlockCount { value: 512, games: 8 },
+            MaxBlockCount { value: 2048, games: 2 },
+        ]);
+    }
+
+    #[test]
+    fn formats() {
+        let report = Report::new(&Strategy::Greedy, 3, &[result(1200, 128), result(800, 64)]);
+
+        let table = report.format(ReportFormat::Table);
+
+        assert!(table.contains("win rate        0.0%"));
+        assert!(table.contains("      128         1    50.0%"));
+
+        let json: serde_json::Value = serde_json::from_str(&report.format(ReportFormat::Json)).unwrap();
+
+        assert_eq!(json["strategy"], "greedy");
+        assert_eq!(json["max_blocks"][1]["value"], 128);
+
+        let csv = report.format(ReportFormat::Csv);
+
+        assert!(csv.starts_with("statistic,value\nstrategy,greedy\n"));
+        assert!(csv.contains("average_score,1000\n"));
+        assert!(csv.ends_with("max_block_64,1\nmax_block_128,1\n"));
+    }
+}
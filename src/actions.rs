@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use game_2048_engine::GameMovementDirection;
+
+use crate::constants::*;
+
+/// The letter keys, for bindings given by name.
+const LETTER_KEYS: [KeyCode; 26] = [
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G, KeyCode::H, KeyCode::I,
+    KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N, KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R,
+    KeyCode::S, KeyCode::T, KeyCode::U, KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+];
+
+/// Something the player asks for, whatever the key, button or gesture.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputAction {
+    Move(GameMovementDirection),
+    Undo,
+    Redo,
+    Restart,
+    Hint,
+    Autoplay,
+    /// Picks the highlighted answer of an overlay.
+    Confirm,
+}
+
+impl InputAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let action = match name {
+            "up" => InputAction::Move(GameMovementDirection::Up),
+            "right" => InputAction::Move(GameMovementDirection::Right),
+            "down" => InputAction::Move(GameMovementDirection::Down),
+            "left" => InputAction::Move(GameMovementDirection::Left),
+            "undo" => InputAction::Undo,
+            "redo" => InputAction::Redo,
+            "restart" => InputAction::Restart,
+            "hint" => InputAction::Hint,
+            "autoplay" => InputAction::Autoplay,
+            "confirm" => InputAction::Confirm,
+            _ => return None,
+        };
+
+        Some(action)
+    }
+}
+
+/// A key, key chord or gamepad input an action can be bound to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    /// A key pressed with Control (or Command) held, with or without Shift.
+    ControlKey { key: KeyCode, shift: bool },
+    /// A button of any gamepad.
+    GamepadButton(GamepadButtonType),
+    /// The left stick of any gamepad pushed towards a direction.
+    LeftStick(GameMovementDirection),
+}
+
+impl Binding {
+    /// Reads a letter, an arrow (`up`, `right`, `down`, `left`), `return` or
+    /// `space`, optionally after `ctrl+` or `ctrl+shift+`; a gamepad button
+    /// such as `pad-south`, `pad-up` or `pad-right-trigger`; or a direction
+    /// of the left stick such as `stick-up`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(key) = name.strip_prefix("ctrl+shift+") {
+            return key_from_name(key).map(|key| Binding::ControlKey { key, shift: true });
+        }
+
+        if let Some(key) = name.strip_prefix("ctrl+") {
+            return key_from_name(key).map(|key| Binding::ControlKey { key, shift: false });
+        }
+
+        if let Some(direction) = name.strip_prefix("stick-") {
+            return match InputAction::from_name(direction) {
+                Some(InputAction::Move(direction)) => Some(Binding::LeftStick(direction)),
+                _ => None,
+            };
+        }
+
+        if let Some(button) = name.strip_prefix("pad-") {
+            let button = match button {
+                "south" => GamepadButtonType::South,
+                "east" => GamepadButtonType::East,
+                "north" => GamepadButtonType::North,
+                "west" => GamepadButtonType::West,
+                "left-trigger" => GamepadButtonType::LeftTrigger,
+                "right-trigger" => GamepadButtonType::RightTrigger,
+                "select" => GamepadButtonType::Select,
+                "start" => GamepadButtonType::Start,
+                "up" => GamepadButtonType::DPadUp,
+                "right" => GamepadButtonType::DPadRight,
+                "down" => GamepadButtonType::DPadDown,
+                "left" => GamepadButtonType::DPadLeft,
+                _ => return None,
+            };
+
+            return Some(Binding::GamepadButton(button));
+        }
+
+        key_from_name(name).map(Binding::Key)
+    }
+
+    fn triggered(&self, keyboard_input: &Input<KeyCode>, gamepad_buttons: &Input<GamepadButton>, stick_pushes: &[GameMovementDirection]) -> bool {
+        match *self {
+            Binding::Key(key) => keyboard_input.just_pressed(key),
+            Binding::ControlKey { key, shift } => {
+                let control_pressed = [KeyCode::LControl, KeyCode::RControl, KeyCode::LWin, KeyCode::RWin]
+                    .iter()
+                    .any(|key| keyboard_input.pressed(*key));
+                let shift_pressed = keyboard_input.pressed(KeyCode::LShift) || keyboard_input.pressed(KeyCode::RShift);
+
+                control_pressed && shift_pressed == shift && keyboard_input.just_pressed(key)
+            },
+            Binding::GamepadButton(button_type) => gamepad_buttons.get_just_pressed().any(|button| button.1 == button_type),
+            Binding::LeftStick(direction) => stick_pushes.contains(&direction),
+        }
+    }
+}
+
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    let key = match name {
+        "up" => KeyCode::Up,
+        "right" => KeyCode::Right,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "return" => KeyCode::Return,
+        "space" => KeyCode::Space,
+        _ => {
+            let mut chars = name.chars();
+
+            return match (chars.next(), chars.next()) {
+                (Some(letter @ 'a'..='z'), None) => Some(LETTER_KEYS[(letter as u8 - b'a') as usize]),
+                _ => None,
+            };
+        },
+    };
+
+    Some(key)
+}
+
+/// Which binding triggers which action. A binding triggers one action, an
+/// action can have many bindings.
+pub struct InputBindings {
+    bindings: Vec<(Binding, InputAction)>,
+}
+
+impl InputBindings {
+    /// Binds `binding` to `action`, in place of the action it was bound to.
+    pub fn bind(&mut self, binding: Binding, action: InputAction) {
+        self.unbind(binding);
+        self.bindings.push((binding, action));
+    }
+
+    pub fn unbind(&mut self, binding: Binding) {
+        self.bindings.retain(|(bound, _)| *bound != binding);
+    }
+
+    /// The actions triggered this frame, each once.
+    pub fn triggered(&self, keyboard_input: &Input<KeyCode>, gamepad_buttons: &Input<GamepadButton>, stick_pushes: &[GameMovementDirection]) -> Vec<InputAction> {
+        let mut actions = Vec::new();
+
+        for (binding, action) in self.bindings.iter() {
+            if !actions.contains(action) && binding.triggered(keyboard_input, gamepad_buttons, stick_pushes) {
+                actions.push(*action);
+            }
+        }
+
+        actions
+    }
+}
+
+impl Default for InputBindings {
+    /// Arrow keys, the D-pad and the left stick move. Ctrl+Z, Ctrl+Y and
+    /// Ctrl+Shift+Z undo and redo, H hints, P toggles autoplay and Return
+    /// confirms; no key restarts, a stray press would end the game. On a
+    /// gamepad West undoes, the right trigger redoes, North restarts, East
+    /// hints, Select toggles autoplay and South confirms.
+    fn default() -> Self {
+        let mut bindings = Vec::new();
+
+        for (key, button, direction) in [
+            (KeyCode::Up, GamepadButtonType::DPadUp, GameMovementDirection::Up),
+            (KeyCode::Right, GamepadButtonType::DPadRight, GameMovementDirection::Right),
+            (KeyCode::Down, GamepadButtonType::DPadDown, GameMovementDirection::Down),
+            (KeyCode::Left, GamepadButtonType::DPadLeft, GameMovementDirection::Left),
+        ].iter().copied() {
+            bindings.push((Binding::Key(key), InputAction::Move(direction)));
+            bindings.push((Binding::GamepadButton(button), InputAction::Move(direction)));
+            bindings.push((Binding::LeftStick(direction), InputAction::Move(direction)));
+        }
+
+        bindings.extend_from_slice(&[
+            (Binding::ControlKey { key: KeyCode::Z, shift: false }, InputAction::Undo),
+            (Binding::ControlKey { key: KeyCode::Y, shift: false }, InputAction::Redo),
+            (Binding::ControlKey { key: KeyCode::Z, shift: true }, InputAction::Redo),
+            (Binding::Key(KeyCode::H), InputAction::Hint),
+            (Binding::Key(KeyCode::P), InputAction::Autoplay),
+            (Binding::Key(KeyCode::Return), InputAction::Confirm),
+            (Binding::GamepadButton(GamepadButtonType::West), InputAction::Undo),
+            (Binding::GamepadButton(GamepadButtonType::RightTrigger), InputAction::Redo),
+            (Binding::GamepadButton(GamepadButtonType::North), InputAction::Restart),
+            (Binding::GamepadButton(GamepadButtonType::East), InputAction::Hint),
+            (Binding::GamepadButton(GamepadButtonType::Select), InputAction::Autoplay),
+            (Binding::GamepadButton(GamepadButtonType::South), InputAction::Confirm),
+        ]);
+
+        InputBindings { bindings }
+    }
+}
+
+/// Turns a stick position into single pushes. The stick is pushed once it
+/// leaves the `STICK_PUSH_THRESHOLD` deadzone and stays pushed until it comes
+/// back under `STICK_RELEASE_THRESHOLD`, so holding it or wobbling near the
+/// edge of the deadzone makes one move.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct StickLatch {
+    pushed: Option<GameMovementDirection>,
+}
+
+impl StickLatch {
+    /// Takes the stick position, y up, and returns the direction of a new push.
+    pub fn update(&mut self, x: f32, y: f32) -> Option<GameMovementDirection> {
+        let distance = x.abs().max(y.abs());
+
+        if self.pushed.is_some() {
+            if distance < STICK_RELEASE_THRESHOLD {
+                self.pushed = None;
+            }
+
+            return None;
+        }
+
+        if distance < STICK_PUSH_THRESHOLD {
+            return None;
+        }
+
+        let direction = match (x.abs() > y.abs(), x > 0.0, y > 0.0) {
+            (true, true, _) => GameMovementDirection::Right,
+            (true, false, _) => GameMovementDirection::Left,
+            (false, _, true) => GameMovementDirection::Up,
+            (false, _, false) => GameMovementDirection::Down,
+        };
+
+        self.pushed = Some(direction);
+
+        Some(direction)
+    }
+}
+
+/// The gamepads plugged in and the state of their left sticks.
+#[derive(Default)]
+pub struct GamepadSticks {
+    latches: HashMap<Gamepad, StickLatch>,
+    event_reader: EventReader<GamepadEvent>,
+}
+
+/// Reads the keyboard and the gamepads and sends the actions their
+/// bindings trigger.
+pub fn input_actions(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_events: Res<Events<GamepadEvent>>,
+    bindings: Res<InputBindings>,
+    mut gamepad_sticks: Local<GamepadSticks>,
+    mut actions: ResMut<Events<InputAction>>,
+) {
+    let GamepadSticks { latches, event_reader } = &mut *gamepad_sticks;
+
+    for GamepadEvent(gamepad, event_type) in event_reader.iter(&gamepad_events) {
+        match event_type {
+            GamepadEventType::Connected => {
+                latches.insert(*gamepad, StickLatch::default());
+            },
+            GamepadEventType::Disconnected => {
+                latches.remove(gamepad);
+            },
+            _ => {},
+        }
+    }
+
+    let stick_pushes: Vec<GameMovementDirection> = latches.iter_mut()
+        .filter_map(|(gamepad, latch)| {
+            let x = gamepad_axes.get(GamepadAxis(*gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.0);
+            let y = gamepad_axes.get(GamepadAxis(*gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.0);
+
+            latch.update(x, y)
+        })
+        .collect();
+
+    for action in bindings.triggered(&keyboard_input, &gamepad_buttons, &stick_pushes) {
+        actions.send(action);
+    }
+}
+
+#[cfg(test)]
+mod actions_tests {
+    use super::*;
+
+    fn triggered(bindings: &InputBindings, keys: &[KeyCode], buttons: &[GamepadButtonType]) -> Vec<InputAction> {
+        let mut keyboard_input = Input::default();
+        let mut gamepad_buttons = Input::default();
+
+        for key in keys.iter() {
+            keyboard_input.press(*key);
+        }
+
+        for button in buttons.iter() {
+            gamepad_buttons.press(GamepadButton(Gamepad(0), *button));
+        }
+
+        bindings.triggered(&keyboard_input, &gamepad_buttons, &[])
+    }
+
+    #[test]
+    fn default_bindings() {
+        let bindings = InputBindings::default();
+
+        assert_eq!(triggered(&bindings, &[KeyCode::Left], &[]), vec![InputAction::Move(GameMovementDirection::Left)]);
+        assert_eq!(triggered(&bindings, &[], &[GamepadButtonType::DPadUp]), vec![InputAction::Move(GameMovementDirection::Up)]);
+        assert_eq!(triggered(&bindings, &[KeyCode::LControl, KeyCode::Z], &[]), vec![InputAction::Undo]);
+        assert_eq!(triggered(&bindings, &[KeyCode::RWin, KeyCode::LShift, KeyCode::Z], &[]), vec![InputAction::Redo]);
+        assert_eq!(triggered(&bindings, &[KeyCode::Z], &[]), vec![]);
+        assert_eq!(
+            triggered(&bindings, &[], &[GamepadButtonType::West, GamepadButtonType::East, GamepadButtonType::North]),
+            vec![InputAction::Undo, InputAction::Restart, InputAction::Hint],
+        );
+        assert_eq!(
+            bindings.triggered(&Input::default(), &Input::default(), &[GameMovementDirection::Down]),
+            vec![InputAction::Move(GameMovementDirection::Down)],
+        );
+    }
+
+    #[test]
+    fn remapping() {
+        let mut bindings = InputBindings::default();
+
+        bindings.bind(Binding::Key(KeyCode::W), InputAction::Move(GameMovementDirection::Up));
+        bindings.bind(Binding::Key(KeyCode::H), InputAction::Restart);
+        bindings.unbind(Binding::GamepadButton(GamepadButtonType::West));
+
+        assert_eq!(triggered(&bindings, &[KeyCode::W], &[]), vec![InputAction::Move(GameMovementDirection::Up)]);
+        assert_eq!(triggered(&bindings, &[KeyCode::H], &[]), vec![InputAction::Restart]);
+        assert_eq!(triggered(&bindings, &[], &[GamepadButtonType::West]), vec![]);
+        assert_eq!(triggered(&bindings, &[], &[GamepadButtonType::East]), vec![InputAction::Hint]);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Binding::from_name("w"), Some(Binding::Key(KeyCode::W)));
+        assert_eq!(Binding::from_name("up"), Some(Binding::Key(KeyCode::Up)));
+        assert_eq!(Binding::from_name("ctrl+u"), Some(Binding::ControlKey { key: KeyCode::U, shift: false }));
+        assert_eq!(Binding::from_name("ctrl+shift+u"), Some(Binding::ControlKey { key: KeyCode::U, shift: true }));
+        assert_eq!(Binding::from_name("pad-start"), Some(Binding::GamepadButton(GamepadButtonType::Start)));
+        assert_eq!(Binding::from_name("pad-left"), Some(Binding::GamepadButton(GamepadButtonType::DPadLeft)));
+        assert_eq!(Binding::from_name("stick-down"), Some(Binding::LeftStick(GameMovementDirection::Down)));
+
+        for name in ["ww", "W", "ctrl+", "pad-x", "stick-undo", ""].iter() {
+            assert_eq!(Binding::from_name(name), None, "{}", name);
+        }
+
+        assert_eq!(InputAction::from_name("left"), Some(InputAction::Move(GameMovementDirection::Left)));
+        assert_eq!(InputAction::from_name("autoplay"), Some(InputAction::Autoplay));
+        assert_eq!(InputAction::from_name("quit"), None);
+    }
+
+    #[test]
+    fn stick_pushes() {
+        let mut latch = StickLatch::default();
+
+        // inside the deadzone
+        assert_eq!(latch.update(0.2, -0.4), None);
+        assert_eq!(latch.update(0.9, 0.3), Some(GameMovementDirection::Right));
+
+        // held, then wobbling between the two thresholds
+        assert_eq!(latch.update(1.0, 0.0), None);
+        assert_eq!(latch.update(0.4, 0.0), None);
+        assert_eq!(latch.update(0.8, 0.0), None);
+
+        // turning without letting go is still the same push
+        assert_eq!(latch.update(0.0, 1.0), None);
+
+        assert_eq!(latch.update(0.1, 0.1), None);
+        assert_eq!(latch.update(-0.2, -0.7), Some(GameMovementDirection::Down));
+    }
+}
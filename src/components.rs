@@ -1,8 +1,12 @@
 use std::time::Duration;
 use bevy::prelude::*;
+use game_2048_engine::GameBoard;
 
 use crate::constants::*;
 
+pub struct Block;
+pub struct BlockPlaceholder;
+
 pub struct AnimateBlockTimer(pub Timer);
 
 impl Default for AnimateBlockTimer {
@@ -12,3 +16,32 @@ impl Default for AnimateBlockTimer {
         )
     }
 }
+
+#[derive(Clone, Copy)]
+pub enum ScoreText {
+    Current,
+    Best,
+}
+
+impl ScoreText {
+    pub fn value(&self, game_board: &GameBoard) -> String {
+        match self {
+            ScoreText::Current => format!("Score: {}", game_board.score()),
+            ScoreText::Best => format!("Best: {}", game_board.best_score()),
+        }
+    }
+}
+
+pub struct Overlay;
+
+pub struct HintText;
+
+pub struct HintArrow;
+
+#[derive(Clone, Copy)]
+pub enum OverlayButton {
+    KeepGoing,
+    TryAgain,
+    Continue,
+    NewGame,
+}
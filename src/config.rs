@@ -0,0 +1,255 @@
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+use game_2048_cli::{parse_block_count, parse_board_size, parse_seed, parse_solver_depth, parse_spawn_distribution};
+use game_2048_engine::{Solver, SpawnPolicy};
+
+use crate::actions::{Binding, InputAction, InputBindings};
+use crate::constants::*;
+
+/// Game options read from the command line.
+pub struct GameConfig {
+    pub width: u8,
+    pub height: u8,
+    /// Seed for block placement, random when not given.
+    pub seed: Option<u64>,
+    pub spawn_policy: SpawnPolicy,
+    /// Search used for hints and autoplay.
+    pub solver: Solver,
+    /// Replay file to play back instead of playing a game.
+    pub replay: Option<PathBuf>,
+    /// Moves per second during replay playback.
+    pub replay_speed: f32,
+    /// The default bindings with the `--bind` options applied.
+    pub bindings: InputBindings,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            width: DEFAULT_COLS_COUNT,
+            height: DEFAULT_ROWS_COUNT,
+            seed: None,
+            spawn_policy: SpawnPolicy::default(),
+            solver: Solver::default(),
+            replay: None,
+            replay_speed: DEFAULT_REPLAY_SPEED,
+            bindings: InputBindings::default(),
+        }
+    }
+}
+
+const USAGE: &str = "usage: game_2048 [--size <cols>x<rows>] [--seed <number>] \
+    [--spawn <value>:<weight>,...] [--spawn-per-move <count>] [--initial-blocks <count>] [--solver-depth <moves>] \
+    [--bind <input>=<action>,...] [--replay <file> [--replay-speed <moves per second>]]";
+
+impl GameConfig {
+    pub fn from_args() -> Self {
+        match GameConfig::parse(env::args().skip(1)) {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("{}\n{}", err, USAGE);
+
+                process::exit(2);
+            }
+        }
+    }
+
+    fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut config = GameConfig::default();
+
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--size" => {
+                    let value = args.next().ok_or("--size requires a value")?;
+
+                    let (width, height) = parse_board_size(&value)?;
+
+                    config.width = width;
+                    config.height = height;
+                },
+                "--seed" => {
+                    let value = args.next().ok_or("--seed requires a value")?;
+
+                    config.seed = Some(parse_seed(&value)?);
+                },
+                "--spawn" => {
+                    let value = args.next().ok_or("--spawn requires a value")?;
+
+                    config.spawn_policy.distribution = parse_spawn_distribution(&value)?;
+                },
+                "--spawn-per-move" => {
+                    let value = args.next().ok_or("--spawn-per-move requires a value")?;
+
+                    config.spawn_policy.blocks_per_move = parse_block_count(&value)?;
+                },
+                "--initial-blocks" => {
+                    let value = args.next().ok_or("--initial-blocks requires a value")?;
+
+                    config.spawn_policy.initial_blocks = parse_block_count(&value)?;
+                },
+                "--solver-depth" => {
+                    let value = args.next().ok_or("--solver-depth requires a value")?;
+
+                    config.solver.depth = parse_solver_depth(&value)?;
+                },
+                "--bind" => {
+                    let value = args.next().ok_or("--bind requires a value")?;
+
+                    for (binding, action) in parse_bindings(&value)? {
+                        config.bindings.bind(binding, action);
+                    }
+                },
+                "--replay" => {
+                    let value = args.next().ok_or("--replay requires a file")?;
+
+                    config.replay = Some(PathBuf::from(value));
+                },
+                "--replay-speed" => {
+                    let value = args.next().ok_or("--replay-speed requires a value")?;
+
+                    config.replay_speed = value.parse::<f32>()
+                        .ok()
+                        .filter(|speed| *speed > 0.0 && speed.is_finite())
+                        .ok_or(format!("invalid replay speed: {}, expected a positive number", value))?;
+                },
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+
+        // the options may come in any order, the policy is checked as a whole
+        let SpawnPolicy { distribution, blocks_per_move, initial_blocks } = config.spawn_policy;
+
+        config.spawn_policy = SpawnPolicy::new(distribution, blocks_per_move, initial_blocks)?;
+
+        Ok(config)
+    }
+}
+
+fn parse_bindings(value: &str) -> Result<Vec<(Binding, InputAction)>, String> {
+    value.split(',')
+        .map(|entry| {
+            let mut parts = entry.split('=');
+
+            let binding = parts.next().and_then(Binding::from_name);
+            let action = parts.next().and_then(InputAction::from_name);
+
+            match (binding, action, parts.next()) {
+                (Some(binding), Some(action), None) => Ok((binding, action)),
+                _ => Err(format!(
+                    "invalid binding: {}, expected <input>=<action> like w=up or pad-start=restart",
+                    entry,
+                )),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod config_tests {
+    use bevy::prelude::*;
+    use game_2048_engine::BlockSize;
+
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<GameConfig, String> {
+        GameConfig::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn board_size() {
+        let config = parse(&[]).unwrap();
+
+        assert_eq!((config.width, config.height), (DEFAULT_COLS_COUNT, DEFAULT_ROWS_COUNT));
+
+        let config = parse(&["--size", "6x6"]).unwrap();
+
+        assert_eq!((config.width, config.height), (6, 6));
+
+        assert!(parse(&["--size"]).is_err());
+        assert!(parse(&["--size", "1x4"]).is_err());
+        assert!(parse(&["--size", "4x4x4"]).is_err());
+        assert!(parse(&["--size", "four"]).is_err());
+        assert!(parse(&["--color"]).is_err());
+        assert!(parse(&["simulate"]).is_err());
+    }
+
+    #[test]
+    fn seed() {
+        assert_eq!(parse(&[]).unwrap().seed, None);
+        assert_eq!(parse(&["--seed", "2048"]).unwrap().seed, Some(2048));
+        assert_eq!(parse(&["--size", "5x5", "--seed", "7"]).unwrap().seed, Some(7));
+
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--seed", "-1"]).is_err());
+    }
+
+    #[test]
+    fn spawn_policy() {
+        assert_eq!(parse(&[]).unwrap().spawn_policy, SpawnPolicy::default());
+
+        let config = parse(&["--spawn", "2:3,8:1", "--spawn-per-move", "2", "--initial-blocks", "4"]).unwrap();
+
+        assert_eq!(config.spawn_policy, SpawnPolicy {
+            distribution: vec![
+                (BlockSize::from_value(2).unwrap(), 3),
+                (BlockSize::from_value(8).unwrap(), 1),
+            ],
+            blocks_per_move: 2,
+            initial_blocks: 4,
+        });
+
+        assert!(parse(&["--spawn", "3:1"]).is_err());
+        assert!(parse(&["--spawn", "2:0"]).is_err());
+        assert!(parse(&["--spawn", "2"]).is_err());
+        assert!(parse(&["--spawn-per-move", "0"]).is_err());
+        assert!(parse(&["--initial-blocks", "0"]).is_err());
+
+        let config = parse(&["--spawn", "2:4294967295,4:1"]).unwrap();
+
+        assert_eq!(config.spawn_policy.total_weight(), u64::from(u32::MAX) + 1);
+        assert!(parse(&["--initial-blocks", "many"]).is_err());
+    }
+
+    #[test]
+    fn solver_depth() {
+        assert_eq!(parse(&[]).unwrap().solver.depth, Solver::default().depth);
+        assert_eq!(parse(&["--solver-depth", "3"]).unwrap().solver.depth, 3);
+
+        assert!(parse(&["--solver-depth", "0"]).is_err());
+        assert!(parse(&["--solver-depth", "9"]).is_err());
+    }
+
+    #[test]
+    fn bindings() {
+        let config = parse(&["--bind", "w=up,ctrl+u=undo", "--bind", "pad-start=restart"]).unwrap();
+        let (keyboard_input, mut gamepad_buttons) = (Input::default(), Input::default());
+
+        gamepad_buttons.press(GamepadButton(Gamepad(0), GamepadButtonType::Start));
+
+        assert_eq!(config.bindings.triggered(&keyboard_input, &gamepad_buttons, &[]), vec![InputAction::Restart]);
+
+        assert!(parse(&["--bind"]).is_err());
+        assert!(parse(&["--bind", "w"]).is_err());
+        assert!(parse(&["--bind", "w=jump"]).is_err());
+        assert!(parse(&["--bind", "w=up,f1=hint"]).is_err());
+    }
+
+    #[test]
+    fn replay() {
+        let config = parse(&[]).unwrap();
+
+        assert_eq!(config.replay, None);
+        assert_eq!(config.replay_speed, DEFAULT_REPLAY_SPEED);
+
+        let config = parse(&["--replay", "game.json", "--replay-speed", "0.5"]).unwrap();
+
+        assert_eq!(config.replay, Some(PathBuf::from("game.json")));
+        assert_eq!(config.replay_speed, 0.5);
+
+        assert!(parse(&["--replay"]).is_err());
+        assert!(parse(&["--replay-speed", "0"]).is_err());
+        assert!(parse(&["--replay-speed", "fast"]).is_err());
+    }
+}
@@ -1,16 +1,26 @@
 pub const BLOCK_SIZE: f32 = 80.0;
-pub const ROWS_COUNT: u8 = 4;
-pub const COLS_COUNT: u8 = 4;
+pub const DEFAULT_ROWS_COUNT: u8 = 4;
+pub const DEFAULT_COLS_COUNT: u8 = 4;
 pub const GAP: f32 = 40.0;
 pub const BLOCK_TEXT_SIZE: f32 = 40.0;
-pub const WINDOW_HEIGHT: f32 = BLOCK_SIZE * ROWS_COUNT as f32 + GAP * 2.0 + GAP * (ROWS_COUNT - 1) as f32;
-pub const WINDOW_WIDTH: f32 = BLOCK_SIZE * COLS_COUNT as f32 + GAP * 2.0 + GAP * (COLS_COUNT - 1) as f32;
+pub const SCORE_PANEL_HEIGHT: f32 = 80.0;
+pub const SCORE_BOX_WIDTH: f32 = 160.0;
+pub const SCORE_BOX_HEIGHT: f32 = 48.0;
+pub const SCORE_TEXT_SIZE: f32 = 24.0;
+pub const OVERLAY_TITLE_TEXT_SIZE: f32 = 60.0;
+pub const OVERLAY_BUTTON_WIDTH: f32 = 160.0;
+pub const OVERLAY_BUTTON_HEIGHT: f32 = 48.0;
+pub const UNDO_HISTORY_LENGTH: usize = 64;
 pub const BLOCK_SPAWN_ANIMATION_DURATION_MILLIS: u64 = 200;
-
-#[derive(Clone, Copy, Debug)]
-pub enum GameMovementDirection {
-    Up,
-    Right,
-    Down,
-    Left,
-}
+pub const DEFAULT_REPLAY_SPEED: f32 = 4.0;
+pub const HINT_TEXT_SIZE: f32 = 20.0;
+pub const HINT_ARROW_SIZE: f32 = 20.0;
+/// How far a drag has to go, in logical pixels, to count as a swipe.
+pub const SWIPE_MIN_DISTANCE: f32 = 40.0;
+/// How many times longer than the other axis the axis of a swipe has to be.
+pub const SWIPE_AXIS_RATIO: f32 = 1.5;
+/// How far the left stick has to be pushed, from 0 to 1, to make a move.
+pub const STICK_PUSH_THRESHOLD: f32 = 0.6;
+/// How far back towards the centre the stick has to come before it can make
+/// another move.
+pub const STICK_RELEASE_THRESHOLD: f32 = 0.3;
@@ -2,3 +2,6 @@
 pub struct BoardMoveStart;
 pub struct BoardMoveEnd;
 pub struct GameOverEvent;
+pub struct GameWonEvent;
+pub struct NewGameEvent;
+pub struct BoardRestored;
@@ -0,0 +1,384 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::prelude::*;
+use bevy::render::texture::{Extent3d, TextureDimension, TextureFormat};
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+use game_2048_engine::{BoardKey, GameBoard, GameMovementDirection, Solver, SpawnPolicy};
+
+use crate::actions::InputAction;
+use crate::components::*;
+use crate::constants::*;
+use crate::events::*;
+use crate::layout::BoardLayout;
+use crate::materials::Materials;
+use crate::{GameMovement, GameState};
+
+/// A move the solver suggested and the board it was suggested for.
+#[derive(Clone, Debug)]
+pub struct Suggestion {
+    pub direction: GameMovementDirection,
+    /// How good the solver expects the board to be after the move.
+    pub score: f32,
+    /// The board the suggestion was computed for, it is stale on any other.
+    pub key: BoardKey,
+}
+
+impl Suggestion {
+    fn search(solver: &Solver, game_board: &GameBoard, spawn_policy: &SpawnPolicy) -> Option<Suggestion> {
+        solver.best_move(game_board, spawn_policy)
+            .map(|(direction, score)| Suggestion {
+                direction,
+                score,
+                key: game_board.key(),
+            })
+    }
+}
+
+#[derive(Default)]
+pub struct Hint {
+    pub suggestion: Option<Suggestion>,
+    /// Plays the suggested move whenever the board is idle.
+    pub autoplay: bool,
+    /// Asks `hint_search` for a suggestion for the board as it is now.
+    requested: bool,
+    /// The search running in the background and the board it searches from.
+    search: Option<(BoardKey, Task<Option<Suggestion>>)>,
+}
+
+impl Hint {
+    /// The suggestion for the board as it is now.
+    fn current(&self, game_board: &GameBoard) -> Option<&Suggestion> {
+        self.suggestion.as_ref().filter(|suggestion| suggestion.key == game_board.key())
+    }
+
+    fn text(&self, game_board: &GameBoard) -> String {
+        let score = self.current(game_board).map(|suggestion| suggestion.score);
+
+        match (self.autoplay, score) {
+            (true, Some(score)) => format!("Autoplay ({:.0})", score),
+            (true, None) => "Autoplay".to_string(),
+            (false, Some(score)) => format!("Hint ({:.0})", score),
+            (false, None) => String::new(),
+        }
+    }
+}
+
+/// The hint action asks the solver for a move, the autoplay action toggles autoplay.
+pub fn hint_input(
+    actions: Res<Events<InputAction>>,
+    game_movement: Res<GameMovement>,
+    game_state: Res<GameState>,
+    mut hint: ResMut<Hint>,
+    mut action_reader: Local<EventReader<InputAction>>,
+) {
+    let actions: Vec<InputAction> = action_reader.iter(&actions).copied().collect();
+
+    if !game_state.accepts_input() {
+        return;
+    }
+
+    if actions.contains(&InputAction::Autoplay) {
+        hint.autoplay = !hint.autoplay;
+    }
+
+    // the board already holds the next state while the blocks move
+    if game_movement.direction.is_some() || game_movement.move_timer.is_some() {
+        return;
+    }
+
+    if actions.contains(&InputAction::Hint) {
+        hint.requested = true;
+    }
+}
+
+/// Runs the requested searches on the async compute pool, so that deep
+/// searches do not freeze the window, and picks up their suggestions.
+pub fn hint_search(
+    pool: Res<AsyncComputeTaskPool>,
+    game_board: Res<GameBoard>,
+    spawn_policy: Res<SpawnPolicy>,
+    solver: Res<Solver>,
+    mut hint: ResMut<Hint>,
+) {
+    let finished = match &mut hint.search {
+        Some((_, task)) => future::block_on(future::poll_once(task)),
+        None => None,
+    };
+
+    if let Some(suggestion) = finished {
+        // no move left to play
+        if suggestion.is_none() {
+            hint.autoplay = false;
+        }
+
+        hint.search = None;
+        hint.suggestion = suggestion;
+    }
+
+    if !hint.requested {
+        return;
+    }
+
+    hint.requested = false;
+
+    let key = game_board.key();
+    let searching = matches!(&hint.search, Some((search_key, _)) if *search_key == key);
+
+    if hint.current(&game_board).is_some() || searching {
+        return;
+    }
+
+    let solver = solver.clone();
+    let game_board = game_board.clone();
+    let spawn_policy = spawn_policy.clone();
+
+    // replacing a search for an older board drops, and so cancels, it
+    hint.search = Some((key, pool.spawn(async move {
+        Suggestion::search(&solver, &game_board, &spawn_policy)
+    })));
+}
+
+/// Drops the suggestion and the running search when the board is replaced
+/// rather than moved, by an undo or a new game.
+pub fn hint_cleaner(
+    new_game_events: Res<Events<NewGameEvent>>,
+    board_restored_events: Res<Events<BoardRestored>>,
+    mut hint: ResMut<Hint>,
+    mut new_game_reader: Local<EventReader<NewGameEvent>>,
+    mut board_restored_reader: Local<EventReader<BoardRestored>>,
+) {
+    let new_game = new_game_reader.iter(&new_game_events).next().is_some();
+    let board_restored = board_restored_reader.iter(&board_restored_events).next().is_some();
+
+    if new_game || board_restored {
+        hint.suggestion = None;
+        hint.search = None;
+    }
+}
+
+pub fn autoplayer(
+    game_state: Res<GameState>,
+    game_board: Res<GameBoard>,
+    mut game_movement: ResMut<GameMovement>,
+    mut hint: ResMut<Hint>,
+) {
+    if !hint.autoplay || !game_state.accepts_input() {
+        return;
+    }
+
+    if game_movement.direction.is_some() || game_movement.move_timer.is_some() {
+        return;
+    }
+
+    match hint.current(&game_board) {
+        Some(suggestion) => game_movement.start(suggestion.direction),
+        None => hint.requested = true,
+    }
+}
+
+/// Where the arrow pointing up has a pixel, row by row from the top of a
+/// `size` by `size` image: a triangle over the top half and a shaft below.
+fn arrow_pixels(size: u32) -> Vec<bool> {
+    let center = size as f32 / 2.0;
+
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let distance = (x as f32 + 0.5 - center).abs();
+
+            if (y as f32) < center {
+                distance <= y as f32 + 0.5
+            } else {
+                distance <= size as f32 / 6.0
+            }
+        })
+        .collect()
+}
+
+fn arrow_texture(size: u32) -> Texture {
+    let data = arrow_pixels(size)
+        .into_iter()
+        .flat_map(|pixel| if pixel { vec![255; 4] } else { vec![0; 4] })
+        .collect();
+
+    Texture::new(Extent3d::new(size, size, 1), TextureDimension::D2, data, TextureFormat::Rgba8UnormSrgb)
+}
+
+/// How far the arrow pointing up turns, counterclockwise, to point in `direction`.
+fn arrow_angle(direction: GameMovementDirection) -> f32 {
+    match direction {
+        GameMovementDirection::Up => 0.0,
+        GameMovementDirection::Right => -FRAC_PI_2,
+        GameMovementDirection::Down => PI,
+        GameMovementDirection::Left => FRAC_PI_2,
+    }
+}
+
+pub fn hint_text_spawner(
+    commands: &mut Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<Materials>,
+    layout: Res<BoardLayout>,
+    mut textures: ResMut<Assets<Texture>>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let arrow = textures.add(arrow_texture(HINT_ARROW_SIZE as u32));
+    let arrow_material = color_materials.add(ColorMaterial::modulated_texture(arrow, materials.text_primary_color));
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size {
+                height: Val::Px(GAP),
+                width: Val::Px(layout.window_width()),
+            },
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            position_type: PositionType::Absolute,
+            position: Rect {
+                left: Val::Px(0.0),
+                top: Val::Px(SCORE_PANEL_HEIGHT),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        material: materials.transparent_color.clone(),
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        parent.spawn(TextBundle {
+            text: Text {
+                value: String::new(),
+                font: asset_server.load("Roboto-Bold.ttf"),
+                style: TextStyle {
+                    font_size: HINT_TEXT_SIZE,
+                    color: materials.text_primary_color,
+                    alignment: TextAlignment {
+                        horizontal: HorizontalAlign::Center,
+                        vertical: VerticalAlign::Center,
+                    },
+                },
+            },
+            ..Default::default()
+        })
+        .with(HintText);
+
+        parent.spawn(ImageBundle {
+            style: Style {
+                size: Size::new(Val::Px(HINT_ARROW_SIZE), Val::Px(HINT_ARROW_SIZE)),
+                margin: Rect {
+                    left: Val::Px(HINT_ARROW_SIZE / 2.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            material: arrow_material,
+            visible: Visible {
+                is_visible: false,
+                is_transparent: true,
+            },
+            ..Default::default()
+        })
+        .with(HintArrow);
+    });
+}
+
+pub fn hint_text_updater(
+    hint: Res<Hint>,
+    game_board: Res<GameBoard>,
+    mut hint_texts: Query<&mut Text, With<HintText>>,
+    mut hint_arrows: Query<(&mut Transform, &mut Visible), With<HintArrow>>,
+) {
+    let value = hint.text(&game_board);
+    let direction = hint.current(&game_board).map(|suggestion| suggestion.direction);
+
+    for mut text in hint_texts.iter_mut() {
+        // only touch the text when it changes, so it is not laid out every frame
+        if text.value != value {
+            text.value = value.clone();
+        }
+    }
+
+    for (mut transform, mut visible) in hint_arrows.iter_mut() {
+        visible.is_visible = direction.is_some();
+
+        // the layout moves the arrow but leaves its rotation alone
+        if let Some(direction) = direction {
+            transform.rotation = Quat::from_rotation_z(arrow_angle(direction));
+        }
+    }
+}
+
+#[cfg(test)]
+mod hint_tests {
+    use super::*;
+
+    #[test]
+    fn stale_suggestions() {
+        let spawn_policy = SpawnPolicy::default();
+        let mut game_board = GameBoard::with_seed(4, 4, 3);
+        let mut hint = Hint::default();
+
+        game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        assert_eq!(hint.text(&game_board), "");
+
+        let suggestion = Suggestion::search(&Solver::default(), &game_board, &spawn_policy).unwrap();
+
+        hint.suggestion = Some(suggestion.clone());
+
+        assert_eq!(hint.text(&game_board), format!("Hint ({:.0})", suggestion.score));
+
+        game_board.apply_move(suggestion.direction);
+
+        assert!(hint.current(&game_board).is_none());
+        assert_eq!(hint.text(&game_board), "");
+
+        hint.autoplay = true;
+
+        assert_eq!(hint.text(&game_board), "Autoplay");
+    }
+
+    #[test]
+    fn undone_suggestions() {
+        let spawn_policy = SpawnPolicy::default();
+        let mut game_board = GameBoard::with_seed(4, 4, 3);
+        let mut hint = Hint::default();
+
+        game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        let snapshot = game_board.snapshot();
+        let moves = game_board.moves();
+        let suggestion = Suggestion::search(&Solver::default(), &game_board, &spawn_policy).unwrap();
+
+        hint.suggestion = Some(suggestion.clone());
+
+        // undo the suggested move and play another one, the move count ends up the same
+        game_board.apply_move(suggestion.direction);
+        game_board.restore(&snapshot);
+
+        let other_direction = game_board.legal_moves()
+            .into_iter()
+            .find(|direction| *direction != suggestion.direction)
+            .unwrap();
+
+        game_board.apply_move(other_direction);
+        game_board.spawn(&spawn_policy, spawn_policy.blocks_per_move);
+
+        assert_eq!(game_board.moves(), moves + 1);
+        assert!(hint.current(&game_board).is_none());
+        assert_eq!(hint.text(&game_board), "");
+    }
+
+    #[test]
+    fn arrow() {
+        let pixels = arrow_pixels(6);
+        let rows: Vec<String> = pixels.chunks(6)
+            .map(|row| row.iter().map(|pixel| if *pixel { '#' } else { '.' }).collect())
+            .collect();
+
+        assert_eq!(rows, vec!["..##..", ".####.", "######", "..##..", "..##..", "..##.."]);
+        assert_eq!(arrow_angle(GameMovementDirection::Up), 0.0);
+        assert_eq!(arrow_angle(GameMovementDirection::Down), PI);
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use game_2048_engine::{GameBoard, GameSnapshot};
+
+use crate::actions::InputAction;
+use crate::constants::*;
+use crate::events::*;
+use crate::{GameMovement, GameState};
+
+/// Board snapshots taken before each move, newest last.
+pub struct GameHistory {
+    undo_stack: VecDeque<GameSnapshot>,
+    redo_stack: Vec<GameSnapshot>,
+    capacity: usize,
+}
+
+impl GameHistory {
+    pub fn new(capacity: usize) -> Self {
+        GameHistory {
+            undo_stack: VecDeque::with_capacity(capacity),
+            redo_stack: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Remembers the state before a move. A new move makes the undone moves
+    /// unreachable, so the redo stack is dropped.
+    pub fn record(&mut self, snapshot: GameSnapshot) {
+        if self.undo_stack.len() == self.capacity {
+            self.undo_stack.pop_front();
+        }
+
+        self.undo_stack.push_back(snapshot);
+        self.redo_stack.clear();
+    }
+
+    pub fn undo(&mut self, current: GameSnapshot) -> Option<GameSnapshot> {
+        let snapshot = self.undo_stack.pop_back()?;
+
+        self.redo_stack.push(current);
+
+        Some(snapshot)
+    }
+
+    pub fn redo(&mut self, current: GameSnapshot) -> Option<GameSnapshot> {
+        let snapshot = self.redo_stack.pop()?;
+
+        self.undo_stack.push_back(current);
+
+        Some(snapshot)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+}
+
+impl Default for GameHistory {
+    fn default() -> Self {
+        GameHistory::new(UNDO_HISTORY_LENGTH)
+    }
+}
+
+pub fn history_input(
+    actions: Res<Events<InputAction>>,
+    game_movement: Res<GameMovement>,
+    mut game_state: ResMut<GameState>,
+    mut game_history: ResMut<GameHistory>,
+    mut game_board: ResMut<GameBoard>,
+    mut board_restored_events: ResMut<Events<BoardRestored>>,
+    mut action_reader: Local<EventReader<InputAction>>,
+) {
+    let actions: Vec<InputAction> = action_reader.iter(&actions).copied().collect();
+
+    // a replay is played back exactly as it was recorded
+    if *game_state == GameState::Replaying {
+        return;
+    }
+
+    // blocks are still on their way, the board already holds the next state
+    if game_movement.direction.is_some() || game_movement.move_timer.is_some() {
+        return;
+    }
+
+    let snapshot = if actions.contains(&InputAction::Redo) {
+        game_history.redo(game_board.snapshot())
+    } else if actions.contains(&InputAction::Undo) {
+        game_history.undo(game_board.snapshot())
+    } else {
+        None
+    };
+
+    if let Some(snapshot) = snapshot {
+        game_board.restore(&snapshot);
+
+        // stepping back out of a finished game resumes it
+        if !game_state.accepts_input() {
+            *game_state = GameState::resumed_on(&game_board);
+        }
+
+        board_restored_events.send(BoardRestored);
+    }
+}
+
+pub fn history_cleaner(
+    new_game_events: Res<Events<NewGameEvent>>,
+    mut game_history: ResMut<GameHistory>,
+    mut new_game_reader: Local<EventReader<NewGameEvent>>,
+) {
+    if new_game_reader.iter(&new_game_events).next().is_some() {
+        game_history.clear();
+    }
+}
+
+#[cfg(test)]
+mod history_tests {
+    use game_2048_engine::BlockSize;
+
+    use super::*;
+
+    fn snapshot_with(value: u32) -> GameSnapshot {
+        let mut game_board = GameBoard::with_seed(4, 4, 0);
+
+        game_board.set_cell(0, 0, BlockSize::from_value(value));
+
+        game_board.snapshot()
+    }
+
+    fn value_of(snapshot: &GameSnapshot) -> u32 {
+        let mut game_board = GameBoard::new(4, 4);
+
+        game_board.restore(snapshot);
+
+        game_board.get_cell(0, 0).unwrap().value()
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut game_history = GameHistory::new(8);
+
+        game_history.record(snapshot_with(2));
+        game_history.record(snapshot_with(4));
+
+        assert_eq!(game_history.undo(snapshot_with(8)).map(|s| value_of(&s)), Some(4));
+        assert_eq!(game_history.undo(snapshot_with(4)).map(|s| value_of(&s)), Some(2));
+        assert!(game_history.undo(snapshot_with(2)).is_none());
+
+        assert_eq!(game_history.redo(snapshot_with(2)).map(|s| value_of(&s)), Some(4));
+        assert_eq!(game_history.redo(snapshot_with(4)).map(|s| value_of(&s)), Some(8));
+        assert!(game_history.redo(snapshot_with(8)).is_none());
+
+        game_history.undo(snapshot_with(8));
+        game_history.record(snapshot_with(16));
+
+        assert!(game_history.redo(snapshot_with(32)).is_none());
+    }
+
+    #[test]
+    fn bounded() {
+        let mut game_history = GameHistory::new(2);
+
+        game_history.record(snapshot_with(2));
+        game_history.record(snapshot_with(4));
+        game_history.record(snapshot_with(8));
+
+        assert_eq!(game_history.undo(snapshot_with(16)).map(|s| value_of(&s)), Some(8));
+        assert_eq!(game_history.undo(snapshot_with(8)).map(|s| value_of(&s)), Some(4));
+        assert!(game_history.undo(snapshot_with(4)).is_none());
+    }
+}
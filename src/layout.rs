@@ -0,0 +1,39 @@
+use game_2048_engine::GameBoard;
+
+use crate::constants::*;
+
+/// Pixel geometry of the window, derived from the board dimensions.
+#[derive(Clone, Copy)]
+pub struct BoardLayout {
+    cols: u8,
+    rows: u8,
+}
+
+impl BoardLayout {
+    pub fn new(game_board: &GameBoard) -> Self {
+        BoardLayout {
+            cols: game_board.width(),
+            rows: game_board.height(),
+        }
+    }
+
+    pub fn window_width(&self) -> f32 {
+        BLOCK_SIZE * self.cols as f32 + GAP * 2.0 + GAP * (self.cols - 1) as f32
+    }
+
+    pub fn window_height(&self) -> f32 {
+        SCORE_PANEL_HEIGHT + self.board_height()
+    }
+
+    pub fn board_height(&self) -> f32 {
+        BLOCK_SIZE * self.rows as f32 + GAP * 2.0 + GAP * (self.rows - 1) as f32
+    }
+
+    pub fn block_left(&self, x: u8) -> f32 {
+        GAP + (x as f32 * BLOCK_SIZE) + (x as f32 * GAP)
+    }
+
+    pub fn block_top(&self, y: u8) -> f32 {
+        SCORE_PANEL_HEIGHT + GAP + (y as f32 * BLOCK_SIZE) + (y as f32 * GAP)
+    }
+}
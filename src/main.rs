@@ -1,15 +1,10 @@
-use bevy::{app::{ScheduleRunnerPlugin, ScheduleRunnerSettings}, input::system::exit_on_esc_system, prelude::*};
-use std::{borrow::BorrowMut, collections::HashSet, hash::Hash, time::Duration};
+use bevy::{app::{ScheduleRunnerPlugin, ScheduleRunnerSettings}, ecs::SystemParam, input::system::exit_on_esc_system, prelude::*};
+use game_2048_engine::*;
+use std::{borrow::BorrowMut, collections::{HashMap, HashSet}, hash::Hash, process, time::Duration};
 
 mod constants;
 use constants::*;
 
-mod game_board;
-use game_board::*;
-
-mod block;
-use block::*;
-
 mod materials;
 use materials::*;
 
@@ -22,21 +17,85 @@ use components::*;
 mod systems;
 use systems::*;
 
+mod overlay;
+use overlay::*;
+
+mod layout;
+use layout::*;
+
+mod config;
+use config::*;
+
+mod history;
+use history::*;
+
+mod save;
+use save::*;
+
+mod replay;
+use replay::*;
+
+mod hint;
+use hint::*;
+
+mod actions;
+use actions::*;
+
+mod swipe;
+use swipe::*;
+
 struct MoveTimer(Timer);
 
 #[derive(Clone)]
 struct GameMovement {
-    pressed_key: Option<KeyCode>,
     direction: Option<GameMovementDirection>,
     move_timer: Option<Timer>,
+    /// The move being animated, its merges are applied once the blocks arrive.
+    outcome: Option<MoveOutcome>,
+}
+
+impl GameMovement {
+    /// Starts moving the blocks, the move timer drives their animation.
+    fn start(&mut self, direction: GameMovementDirection) {
+        self.direction = Some(direction);
+        self.move_timer = Some(Timer::new(Duration::from_millis(200. as u64), false));
+    }
 }
 
 impl Default for GameMovement {
     fn default() -> Self {
         GameMovement {
-            pressed_key: None,
             direction: None,
             move_timer: None,
+            outcome: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum GameState {
+    Playing,
+    Won,
+    KeepGoing,
+    Over,
+    /// A saved game is on the board, waiting to be continued or replaced.
+    Resuming,
+    /// A recorded game is played back.
+    Replaying,
+}
+
+impl GameState {
+    fn accepts_input(&self) -> bool {
+        matches!(self, GameState::Playing | GameState::KeepGoing)
+    }
+
+    /// The state to play a restored board in: a board that already
+    /// reached the winning block does not announce the win again.
+    fn resumed_on(game_board: &GameBoard) -> Self {
+        if game_board.max_block() >= Some(WIN_BLOCK_SIZE) {
+            GameState::KeepGoing
+        } else {
+            GameState::Playing
         }
     }
 }
@@ -57,19 +116,85 @@ impl Position {
 }
 
 fn main() {
-    App::build()
+    let config = GameConfig::from_args();
+
+    let replay = config.replay.as_ref().map(|path| {
+        Replay::load(path)
+            .and_then(|replay| Ok((replay.start_board()?, replay.spawn_policy()?, replay)))
+            .unwrap_or_else(|err| {
+                eprintln!("could not play {}: {}", path.display(), err);
+
+                process::exit(1);
+            })
+    });
+
+    // the showcase board of debug builds and replays never touch the saved game
+    let saved_game = if cfg!(feature = "debug") || replay.is_some() { None } else { load_game() };
+    let best_score = saved_game.as_ref().map_or(0, GameBoard::best_score);
+
+    // a game asked for on the command line is a new one
+    let resumable_game = saved_game.filter(|saved| {
+        config.seed.is_none()
+            && saved.width() == config.width
+            && saved.height() == config.height
+            && !saved.is_over()
+    });
+
+    let game_state = match (&replay, &resumable_game) {
+        (Some(_), _) => GameState::Replaying,
+        (None, Some(_)) => GameState::Resuming,
+        (None, None) => GameState::Playing,
+    };
+
+    let mut spawn_policy = config.spawn_policy;
+    let mut playback = None;
+
+    let mut game_board = match (replay, resumable_game, config.seed) {
+        (Some((start_board, replay_spawn_policy, replay)), _, _) => {
+            spawn_policy = replay_spawn_policy;
+            playback = Some(ReplayPlayer::new(replay, config.replay_speed));
+
+            start_board
+        },
+        (None, Some(saved), _) => saved,
+        (None, None, Some(seed)) => GameBoard::with_seed(config.width, config.height, seed),
+        (None, None, None) => GameBoard::new(config.width, config.height),
+    };
+
+    game_board.record_best_score(best_score);
+
+    let recording = playback.is_none() && !cfg!(feature = "debug");
+
+    let layout = BoardLayout::new(&game_board);
+
+    let mut app = App::build();
+
+    app
         .add_event::<BoardMoveStart>()
         .add_event::<BoardMoveEnd>()
         .add_event::<GameOverEvent>()
-        .add_resource(ClearColor(Color::rgb_u8(187, 173, 160)))
+        .add_event::<GameWonEvent>()
+        .add_event::<NewGameEvent>()
+        .add_event::<BoardRestored>()
+        .add_event::<InputAction>()
+        .add_resource(ClearColor(Materials::color(palette::BOARD_COLOR)))
         // .add_resource(Msaa { samples: 4 })
         .add_resource(WindowDescriptor {
-            title: "2048".to_string(),
-            height: WINDOW_HEIGHT,
-            width: WINDOW_WIDTH,
+            title: format!("2048 (seed {})", game_board.seed()),
+            height: layout.window_height(),
+            width: layout.window_width(),
             ..Default::default()
         })
+        .add_resource(game_board)
+        .add_resource(layout)
+        .add_resource(spawn_policy)
         .add_resource(GameMovement::default())
+        .add_resource(game_state)
+        .add_resource(GameHistory::default())
+        .add_resource(ReplayRecorder::new(recording))
+        .add_resource(config.solver)
+        .add_resource(Hint::default())
+        .add_resource(config.bindings)
         .add_resource(MoveTimer(Timer::new(
             Duration::from_millis(200. as u64),
             true,
@@ -79,16 +204,55 @@ fn main() {
         // .add_plugin(ScheduleRunnerPlugin {})
         .add_startup_system(setup.system())
         .add_startup_stage("spawn_placeholders", SystemStage::single(placeholders_spawner.system()))
-        // .add_startup_stage("spawn_initial_blocks", SystemStage::single(block_spawner.system()))
-        .add_startup_stage("spawn_debug_blocks", SystemStage::single(debug_block_spawner.system()))
+        .add_startup_stage("spawn_score_panel", SystemStage::single(score_panel_spawner.system()))
+        .add_startup_stage("spawn_hint_text", SystemStage::single(hint_text_spawner.system()))
         .add_system(position_translation.system())
         .add_system(exit_on_esc_system.system())
+        .add_system(input_actions.system())
+        .add_system(swipe_input.system())
         .add_system(input_movement.system())
+        .add_system(restart_input.system())
+        .add_system(hint_cleaner.system())
+        .add_system(hint_input.system())
+        .add_system(autoplayer.system())
+        .add_system(hint_search.system())
+        .add_system(hint_text_updater.system())
         .add_system(movement.system())
         .add_system(game_board_watcher.system())
+        .add_system(game_status_watcher.system())
         .add_system(game_movement_timer_ticker.system())
         .add_system(animate_block_spawned.system())
-        .run();
+        .add_system(score_panel_updater.system())
+        .add_system(game_over_overlay_spawner.system())
+        .add_system(game_won_overlay_spawner.system())
+        .add_system(overlay_buttons.system())
+        .add_system(overlay_cleaner.system())
+        .add_system(new_game.system())
+        .add_system(history_input.system())
+        .add_system(history_cleaner.system())
+        .add_system(board_rebuilder.system())
+        .add_system(replay_recorder_watcher.system())
+        .add_system(replay_writer.system());
+
+    if let Some(player) = playback {
+        app
+            .add_resource(player)
+            .add_system(replay_player.system());
+    }
+
+    #[cfg(not(feature = "debug"))]
+    app.add_startup_stage("start_game", SystemStage::single(game_starter.system()));
+
+    #[cfg(not(feature = "debug"))]
+    if game_state != GameState::Replaying {
+        app.add_system(game_saver.system());
+    }
+
+    // shows one block of every size instead of a regular game
+    #[cfg(feature = "debug")]
+    app.add_startup_stage("spawn_debug_blocks", SystemStage::single(debug_block_spawner.system()));
+
+    app.run();
 }
 
 fn setup(
@@ -101,24 +265,21 @@ fn setup(
 
     commands
         .insert_resource(Materials::instantiate(materials));
-
-    let game_board = GameBoard::new();
-
-    commands
-        .insert_resource(game_board);
 }
 
 fn placeholders_spawner(
     commands: &mut Commands,
     materials: Res<Materials>,
+    game_board: Res<GameBoard>,
+    layout: Res<BoardLayout>,
 ) {
-    for y in 0..ROWS_COUNT {
-        for x in 0..COLS_COUNT {
+    for y in 0..game_board.height() {
+        for x in 0..game_board.width() {
             let pos = Position { x, y };
 
             let mut transform = Transform::from_translation(Vec3::default());
 
-            transform_to_position(&pos, &mut transform);
+            transform_to_position(&layout, &pos, &mut transform);
 
             let sprite_bundle = SpriteBundle {
                 material: materials.empty_color.clone(),
@@ -134,85 +295,160 @@ fn placeholders_spawner(
     }
 }
 
-fn block_spawner(
+fn score_panel_spawner(
     commands: &mut Commands,
     asset_server: Res<AssetServer>,
     materials: Res<Materials>,
-    mut game_board: ResMut<GameBoard>,
+    game_board: Res<GameBoard>,
+    layout: Res<BoardLayout>,
 ) {
-    blocks_spawner(
-        commands,
-        &asset_server,
-        &materials,
-        &mut game_board,
-        vec![
-            (
-                BlockSize::_2,
-                Position {
-                    x: 0,
-                    y: 0,
-                },
-            ),
-            (
-                BlockSize::_2,
-                Position {
-                    x: 1,
-                    y: 3,
+    let font = asset_server.load("Roboto-Bold.ttf");
+
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size {
+                height: Val::Px(SCORE_PANEL_HEIGHT),
+                width: Val::Px(layout.window_width()),
+            },
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::SpaceEvenly,
+            position_type: PositionType::Absolute,
+            position: Rect {
+                left: Val::Px(0.0),
+                top: Val::Px(0.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        material: materials.transparent_color.clone(),
+        ..Default::default()
+    })
+    .with_children(|parent| {
+        for score_text in [ScoreText::Current, ScoreText::Best].iter() {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    size: Size {
+                        height: Val::Px(SCORE_BOX_HEIGHT),
+                        width: Val::Px(SCORE_BOX_WIDTH),
+                    },
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
                 },
-            ),
-        ]
-    );
+                material: materials.score_box_color.clone(),
+                ..Default::default()
+            })
+            .with_children(|parent| {
+                parent.spawn(TextBundle {
+                    text: Text {
+                        value: score_text.value(&game_board),
+                        font: font.clone(),
+                        style: TextStyle {
+                            font_size: SCORE_TEXT_SIZE,
+                            color: materials.text_inverted_color,
+                            alignment: TextAlignment {
+                                horizontal: HorizontalAlign::Center,
+                                vertical: VerticalAlign::Center,
+                            },
+                        },
+                    },
+                    ..Default::default()
+                })
+                .with(*score_text);
+            });
+        }
+    });
 }
 
-fn debug_block_spawner(
+/// Starts a new game, shows the board a replay starts from, or shows
+/// the saved game and asks whether to continue it.
+#[cfg(not(feature = "debug"))]
+fn game_starter(
     commands: &mut Commands,
     asset_server: Res<AssetServer>,
     materials: Res<Materials>,
+    game_board: Res<GameBoard>,
+    game_state: Res<GameState>,
+    mut new_game_events: ResMut<Events<NewGameEvent>>,
+    mut board_restored_events: ResMut<Events<BoardRestored>>,
+) {
+    match *game_state {
+        GameState::Resuming => board_restored_events.send(BoardRestored),
+        GameState::Replaying => return board_restored_events.send(BoardRestored),
+        _ => return new_game_events.send(NewGameEvent),
+    }
+
+    spawn_resume_overlay(commands, asset_server.load("Roboto-Bold.ttf"), &materials, &game_board);
+}
+
+#[cfg(feature = "debug")]
+fn debug_block_spawner(
+    commands: &mut Commands,
+    block_spawner: BlockSpawner,
     mut game_board: ResMut<GameBoard>,
 ) {
     let mut blocks_to_spawn = Vec::new();
 
-    let mut curr_block = BlockSize::_2;
+    let mut curr_block = BlockSize::MIN;
 
-    while let Some(block_size) = curr_block.next() {
-        let (x, y) = game_board.rand_available_cell();
+    while curr_block <= WIN_BLOCK_SIZE {
+        let (x, y) = match game_board.rand_available_cell() {
+            Some(cell) => cell,
+            None => break,
+        };
 
-        blocks_to_spawn.push((
-            curr_block.clone(),
-            Position::new(x, y),
-        ));
+        blocks_to_spawn.push(Position::new(x, y));
 
         game_board.set_cell(x, y, Some(curr_block.clone()));
 
-        curr_block = block_size;
+        curr_block = curr_block.next().unwrap();
     }
 
-    let (x, y) = game_board.rand_available_cell();
+    block_spawner.spawn_entities(commands, &game_board, blocks_to_spawn);
+}
 
-    blocks_to_spawn.push((
-        curr_block.clone(),
-        Position::new(x, y),
-    ));
+/// The resources spawning blocks takes, one system parameter for the
+/// systems that spawn them.
+#[derive(SystemParam)]
+pub struct BlockSpawner<'a> {
+    asset_server: Res<'a, AssetServer>,
+    materials: Res<'a, Materials>,
+    spawn_policy: Res<'a, SpawnPolicy>,
+}
 
-    blocks_spawner(
-        commands,
-        &asset_server,
-        &materials,
-        &mut game_board,
-        blocks_to_spawn
-    );
+impl<'a> BlockSpawner<'a> {
+    /// Spawns an entity for each block of the board at `positions`.
+    fn spawn_entities(&self, commands: &mut Commands, game_board: &GameBoard, positions: Vec<Position>) {
+        blocks_spawner(commands, &self.asset_server, &self.materials, game_board, positions);
+    }
+
+    /// Places `count` new blocks on the board and spawns their entities.
+    fn spawn_blocks(&self, commands: &mut Commands, game_board: &mut GameBoard, count: u8) -> Vec<(BlockSize, (u8, u8))> {
+        let spawned = game_board.spawn(&self.spawn_policy, count);
+
+        self.spawn_entities(commands, game_board, spawned_blocks(&spawned));
+
+        spawned
+    }
 }
 
 fn blocks_spawner(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     materials: &Res<Materials>,
-    game_board: &mut ResMut<GameBoard>,
-    blocks: Vec<(BlockSize, Position)>,
+    game_board: &GameBoard,
+    positions: Vec<Position>,
 ) {
     let font = asset_server.load("Roboto-Bold.ttf");
+    let layout = BoardLayout::new(game_board);
+
+    // an entity per tile on the board, carrying the tile ID
+    let tiles = positions.into_iter()
+        .filter_map(|pos| game_board.get_tile(pos.x, pos.y).map(|tile| (tile, pos)));
+
+    for (tile, block_position) in tiles {
+        let block_size = &tile.block_size;
 
-    for (block_size, block_position) in blocks.iter() {
         let mut node_style = Style {
             size: Size {
                 height: Val::Px(BLOCK_SIZE),
@@ -225,7 +461,7 @@ fn blocks_spawner(
             ..Default::default()
         };
 
-        style_to_position(&block_position, &mut node_style);
+        style_to_position(&layout, &block_position, &mut node_style);
 
         commands.spawn(NodeBundle {
             style: node_style,
@@ -259,30 +495,67 @@ fn blocks_spawner(
                 ..Default::default()
             });
         })
-        .with(block_position.clone())
-        .with(block_size.clone())
+        .with(block_position)
+        .with(tile.block_size)
+        .with(tile.id)
         .with(AnimateBlockTimer::default())
         .with(Block);
-    
-        game_board.set_cell(block_position.x, block_position.y, Some(block_size.clone()));
     }
 }
 
+/// Replaces every block entity with the blocks of the restored board.
+fn board_rebuilder(
+    commands: &mut Commands,
+    block_spawner: BlockSpawner,
+    board_restored_events: Res<Events<BoardRestored>>,
+    game_board: Res<GameBoard>,
+    mut board_restored_reader: Local<EventReader<BoardRestored>>,
+    blocks: Query<Entity, With<Block>>,
+) {
+    if board_restored_reader.iter(&board_restored_events).next().is_none() {
+        return;
+    }
+
+    for entity in blocks.iter() {
+        commands.despawn_recursive(entity);
+    }
+
+    let mut blocks_to_spawn = Vec::new();
+
+    for y in 0..game_board.height() {
+        for x in 0..game_board.width() {
+            if game_board.get_cell(x, y).is_some() {
+                blocks_to_spawn.push(Position::new(x, y));
+            }
+        }
+    }
+
+    block_spawner.spawn_entities(commands, &game_board, blocks_to_spawn);
+}
+
+fn spawned_blocks(spawned: &[(BlockSize, (u8, u8))]) -> Vec<Position> {
+    spawned.iter()
+        .map(|(_, (x, y))| Position::new(*x, *y))
+        .collect()
+}
+
 fn style_to_position(
+    layout: &BoardLayout,
     pos: &Position,
     style: &mut Style,
 ) {
-    style.position.left = Val::Px(GAP + (pos.x as f32 * BLOCK_SIZE) + (pos.x as f32 * GAP));
-    style.position.top = Val::Px(GAP + (pos.y as f32 * BLOCK_SIZE) + (pos.y as f32 * GAP));
+    style.position.left = Val::Px(layout.block_left(pos.x));
+    style.position.top = Val::Px(layout.block_top(pos.y));
 }
 
 fn move_style_to(
+    layout: &BoardLayout,
     pos: &Position,
     style: &mut Style,
     percent: f32,
 ) {
     let new_left = {
-        let dest = GAP + (pos.x as f32 * BLOCK_SIZE) + (pos.x as f32 * GAP);
+        let dest = layout.block_left(pos.x);
         let curr = match style.position.left {
             Val::Px(curr) => curr,
             _ => {
@@ -294,7 +567,7 @@ fn move_style_to(
     };
 
     let new_top = {
-        let dest = GAP + (pos.y as f32 * BLOCK_SIZE) + (pos.y as f32 * GAP);
+        let dest = layout.block_top(pos.y);
         let curr = match style.position.top {
             Val::Px(curr) => curr,
             _ => {
@@ -310,17 +583,20 @@ fn move_style_to(
 }
 
 fn transform_to_position(
+    layout: &BoardLayout,
     pos: &Position,
     transform: &mut Transform,
 ) {
+    // world coordinates grow upwards from the window center while rows grow downwards
     transform.translation = Vec3::new(
-    GAP + (pos.x as f32 * BLOCK_SIZE) + (pos.x as f32 * GAP) - (WINDOW_WIDTH / 2.0) + (BLOCK_SIZE / 2.0),
-    GAP + (pos.y as f32 * BLOCK_SIZE) + (pos.y as f32 * GAP) - (WINDOW_HEIGHT / 2.0) + (BLOCK_SIZE / 2.0),
+    layout.block_left(pos.x) - (layout.window_width() / 2.0) + (BLOCK_SIZE / 2.0),
+    (layout.window_height() / 2.0) - layout.block_top(pos.y) - (BLOCK_SIZE / 2.0),
     0.0
     );
 }
 
 fn position_translation(
+    layout: Res<BoardLayout>,
     mut game_movement: ResMut<GameMovement>,
     mut q: Query<(&Position, &mut Style)>,
     mut board_moved_event: ResMut<Events<BoardMoveEnd>>,
@@ -328,7 +604,7 @@ fn position_translation(
     if let Some(game_movement_timer) = game_movement.move_timer.to_owned() {
         if !game_movement_timer.finished() {
             for (pos, mut style) in q.iter_mut() {
-                move_style_to(pos, &mut style, game_movement_timer.percent());
+                move_style_to(&layout, pos, &mut style, game_movement_timer.percent());
             }
         } else {
             board_moved_event.send(BoardMoveEnd);
@@ -347,130 +623,179 @@ fn game_movement_timer_ticker(
 }
 
 fn input_movement(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut game_movement: ResMut<GameMovement>
+    actions: Res<Events<InputAction>>,
+    game_state: Res<GameState>,
+    mut game_movement: ResMut<GameMovement>,
+    mut action_reader: Local<EventReader<InputAction>>,
 ) {
+    let direction = action_reader.iter(&actions).find_map(|action| match action {
+        InputAction::Move(direction) => Some(*direction),
+        _ => None,
+    });
+
+    if !game_state.accepts_input() {
+        return;
+    }
+
     if game_movement.direction.is_some() {
         return;
     }
-    
-    if let Some(curr_pressed_key) = game_movement.pressed_key {
-        if keyboard_input.just_released(curr_pressed_key) {
-            game_movement.pressed_key = None;
 
-            return;
-        }
+    if let Some(direction) = direction {
+        game_movement.start(direction);
+    }
+}
 
+/// Throws the current game away for a new one, as the new game button of
+/// the overlays does.
+fn restart_input(
+    actions: Res<Events<InputAction>>,
+    game_movement: Res<GameMovement>,
+    mut game_state: ResMut<GameState>,
+    mut new_game_events: ResMut<Events<NewGameEvent>>,
+    mut action_reader: Local<EventReader<InputAction>>,
+) {
+    if !action_reader.iter(&actions).any(|action| *action == InputAction::Restart) {
         return;
     }
 
-    let (direction, pressed_key) = if keyboard_input.pressed(KeyCode::Up) {
-        (Some(GameMovementDirection::Up), Some(KeyCode::Up))
-    } else if keyboard_input.pressed(KeyCode::Right) {
-        (Some(GameMovementDirection::Right), Some(KeyCode::Right))
-    } else if keyboard_input.pressed(KeyCode::Down) {
-        (Some(GameMovementDirection::Down), Some(KeyCode::Down))
-    } else if keyboard_input.pressed(KeyCode::Left) {
-        (Some(GameMovementDirection::Left), Some(KeyCode::Left))
-    } else {
-        (None, None)
-    };
-
-    game_movement.pressed_key = pressed_key;
-    game_movement.direction = direction;
+    if *game_state == GameState::Replaying {
+        return;
+    }
 
-    if pressed_key.is_some() {
-        game_movement.move_timer = Some(Timer::new(Duration::from_millis(200. as u64), false));
+    // the blocks of the old game are still moving
+    if game_movement.direction.is_some() || game_movement.move_timer.is_some() {
+        return;
     }
+
+    *game_state = GameState::Playing;
+
+    new_game_events.send(NewGameEvent);
 }
 
 fn movement(
-    mut positions: Query<(Entity, &mut Position), With<Block>>,
+    mut positions: Query<(&TileId, &mut Position), With<Block>>,
     mut game_movement: ResMut<GameMovement>,
     mut game_board: ResMut<GameBoard>,
+    mut game_history: ResMut<GameHistory>,
+    mut replay_recorder: ResMut<ReplayRecorder>,
+    spawn_policy: Res<SpawnPolicy>,
     mut board_moved_event: ResMut<Events<BoardMoveStart>>,
 ) {
     if let Some(direction) = game_movement.direction {
-        let mut any_block_moved = false;
+        let board_before_move = game_board.clone();
+        let outcome = game_board.apply_move(direction);
 
-        for (_, mut pos) in positions.iter_mut() {
-            let available_steps = game_board.steps(pos.x, pos.y, direction);
+        game_movement.direction = None;
 
-            match direction {
-                GameMovementDirection::Up => {
-                    pos.y -= available_steps;
-                },
-                GameMovementDirection::Right => {
-                    pos.x += available_steps;
-                },
-                GameMovementDirection::Down => {
-                    pos.y += available_steps;
-                },
-                GameMovementDirection::Left => {
-                    pos.x -= available_steps;
-                },
-            }
+        // nothing to animate, and no block spawns after a move that did not happen
+        if !outcome.changed() {
+            game_movement.move_timer = None;
 
-            if available_steps != 0 {
-                any_block_moved = true;
-            }
+            return;
         }
 
-        game_movement.direction = None;
+        let destinations: HashMap<TileId, (u8, u8)> = outcome.motions.iter()
+            .map(|motion| (motion.id, motion.to))
+            .collect();
 
-        if !any_block_moved {
-            return;
+        for (tile_id, mut pos) in positions.iter_mut() {
+            if let Some((x, y)) = destinations.get(tile_id) {
+                *pos = Position::new(*x, *y);
+            }
         }
 
         board_moved_event.send(BoardMoveStart);
 
-        game_board.move_board(direction);
+        game_history.record(board_before_move.snapshot());
+        replay_recorder.record_move(&board_before_move, &spawn_policy, direction);
+
+        game_movement.outcome = Some(outcome);
     }
 }
 
 fn game_board_watcher(
     commands: &mut Commands,
     board_moved_evemts: Res<Events<BoardMoveEnd>>,
-    asset_server: Res<AssetServer>,
-    materials: Res<Materials>,
-    block_sizes: Query<&BlockSize, With<Block>>,
+    block_spawner: BlockSpawner,
+    mut replay_recorder: ResMut<ReplayRecorder>,
+    mut game_movement: ResMut<GameMovement>,
     mut game_board: ResMut<GameBoard>,
-    mut positions: Query<(Entity, &mut Position), With<Block>>,
+    tiles: Query<(Entity, &TileId), With<Block>>,
     mut move_reader: Local<EventReader<BoardMoveEnd>>,
 ) {
     if move_reader.iter(&board_moved_evemts).next().is_none() {
         return;
     }
 
-    let mut despawned_positions: HashSet<Position> = HashSet::new();
-    let mut blocks_to_spawn: Vec<(BlockSize, Position)> = Vec::new();
+    let outcome = match game_movement.outcome.take() {
+        Some(outcome) => outcome,
+        None => return,
+    };
 
-    for (entity, pos) in positions.iter_mut() {
-        let block_size = block_sizes.get(entity).unwrap();
-        let game_board_block_size = game_board.get_cell(pos.x, pos.y).unwrap();
+    // the merging blocks have arrived on top of each other, a block of the
+    // new size with the ID of the one that stayed replaces them
+    let merged_ids: HashSet<TileId> = outcome.merges.iter()
+        .flat_map(|merge| vec![merge.id, merge.merged_id])
+        .collect();
 
-        // blocks merged and now have new size
-        if block_size.ne(&game_board_block_size) {
+    for (entity, tile_id) in tiles.iter() {
+        if merged_ids.contains(tile_id) {
             commands.despawn_recursive(entity);
-
-            if despawned_positions.contains(&Position { x: pos.x, y: pos.y }) {
-                blocks_to_spawn.push((
-                    game_board_block_size.clone(),
-                    pos.clone(),
-                ));
-            } else {
-                despawned_positions.insert(Position { x: pos.x, y: pos.y });
-            }
         }
     }
 
-    if blocks_to_spawn.len() > 0 {
-        blocks_spawner(commands, &asset_server, &materials, &mut game_board, blocks_to_spawn);
+    let blocks_to_spawn: Vec<Position> = outcome.merges.iter()
+        .map(|merge| Position::new(merge.to.0, merge.to.1))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    if !blocks_to_spawn.is_empty() {
+        block_spawner.spawn_entities(commands, &game_board, blocks_to_spawn);
+    }
+
+    let spawned = block_spawner.spawn_blocks(commands, &mut game_board, block_spawner.spawn_policy.blocks_per_move);
+
+    replay_recorder.record_spawns(&spawned);
+}
+
+fn game_status_watcher(
+    board_moved_events: Res<Events<BoardMoveEnd>>,
+    game_board: Res<GameBoard>,
+    game_state: Res<GameState>,
+    mut move_reader: Local<EventReader<BoardMoveEnd>>,
+    mut game_won_events: ResMut<Events<GameWonEvent>>,
+    mut game_over_events: ResMut<Events<GameOverEvent>>,
+) {
+    if move_reader.iter(&board_moved_events).next().is_none() {
+        return;
+    }
+
+    if game_board.is_over() {
+        game_over_events.send(GameOverEvent);
+    } else if *game_state == GameState::Playing && game_board.max_block() >= Some(WIN_BLOCK_SIZE) {
+        game_won_events.send(GameWonEvent);
+    }
+}
+
+fn new_game(
+    commands: &mut Commands,
+    block_spawner: BlockSpawner,
+    new_game_events: Res<Events<NewGameEvent>>,
+    mut game_board: ResMut<GameBoard>,
+    mut new_game_reader: Local<EventReader<NewGameEvent>>,
+    blocks: Query<Entity, With<Block>>,
+) {
+    if new_game_reader.iter(&new_game_events).next().is_none() {
+        return;
+    }
+
+    for entity in blocks.iter() {
+        commands.despawn_recursive(entity);
     }
 
-    let (x, y) = game_board.rand_available_cell();
+    game_board.reset();
 
-    blocks_spawner(commands, &asset_server, &materials, &mut game_board, vec!(
-        (BlockSize::_2, Position::new(x, y)),
-    ))
+    block_spawner.spawn_blocks(commands, &mut game_board, block_spawner.spawn_policy.initial_blocks);
 }
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use game_2048_engine::GameBoard;
+
+use crate::actions::InputAction;
+use crate::components::*;
+use crate::constants::*;
+use crate::events::*;
+use crate::layout::BoardLayout;
+use crate::materials::Materials;
+use crate::GameState;
+
+impl OverlayButton {
+    fn label(&self) -> &'static str {
+        match self {
+            OverlayButton::KeepGoing => "Keep going",
+            OverlayButton::TryAgain => "Try again",
+            OverlayButton::Continue => "Continue",
+            OverlayButton::NewGame => "New game",
+        }
+    }
+}
+
+pub fn game_over_overlay_spawner(
+    commands: &mut Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<Materials>,
+    game_board: Res<GameBoard>,
+    game_over_events: Res<Events<GameOverEvent>>,
+    mut game_state: ResMut<GameState>,
+    mut game_over_reader: Local<EventReader<GameOverEvent>>,
+) {
+    if game_over_reader.iter(&game_over_events).next().is_none() {
+        return;
+    }
+
+    *game_state = GameState::Over;
+
+    spawn_overlay(
+        commands,
+        asset_server.load("Roboto-Bold.ttf"),
+        &materials,
+        &BoardLayout::new(&game_board),
+        "Game over!",
+        format!("Score: {}", game_board.score()),
+        &[OverlayButton::TryAgain],
+    );
+}
+
+pub fn game_won_overlay_spawner(
+    commands: &mut Commands,
+    asset_server: Res<AssetServer>,
+    materials: Res<Materials>,
+    game_board: Res<GameBoard>,
+    game_won_events: Res<Events<GameWonEvent>>,
+    mut game_state: ResMut<GameState>,
+    mut game_won_reader: Local<EventReader<GameWonEvent>>,
+) {
+    if game_won_reader.iter(&game_won_events).next().is_none() {
+        return;
+    }
+
+    *game_state = GameState::Won;
+
+    spawn_overlay(
+        commands,
+        asset_server.load("Roboto-Bold.ttf"),
+        &materials,
+        &BoardLayout::new(&game_board),
+        "You win!",
+        format!("Score: {}", game_board.score()),
+        &[OverlayButton::KeepGoing, OverlayButton::TryAgain],
+    );
+}
+
+pub fn spawn_resume_overlay(
+    commands: &mut Commands,
+    font: Handle<Font>,
+    materials: &Materials,
+    game_board: &GameBoard,
+) {
+    spawn_overlay(
+        commands,
+        font,
+        materials,
+        &BoardLayout::new(game_board),
+        "Welcome back!",
+        format!("Score: {}", game_board.score()),
+        &[OverlayButton::Continue, OverlayButton::NewGame],
+    );
+}
+
+pub fn overlay_buttons(
+    actions: Res<Events<InputAction>>,
+    game_board: Res<GameBoard>,
+    interactions: Query<(&Interaction, &OverlayButton), Mutated<Interaction>>,
+    mut game_state: ResMut<GameState>,
+    mut new_game_events: ResMut<Events<NewGameEvent>>,
+    mut action_reader: Local<EventReader<InputAction>>,
+) {
+    let mut pressed_button = None;
+
+    for (interaction, button) in interactions.iter() {
+        if let Interaction::Clicked = interaction {
+            pressed_button = Some(*button);
+        }
+    }
+
+    if action_reader.iter(&actions).any(|action| *action == InputAction::Confirm) {
+        match *game_state {
+            GameState::Won => pressed_button = Some(OverlayButton::KeepGoing),
+            GameState::Over => pressed_button = Some(OverlayButton::TryAgain),
+            GameState::Resuming => pressed_button = Some(OverlayButton::Continue),
+            _ => {},
+        }
+    }
+
+    match pressed_button {
+        Some(OverlayButton::KeepGoing) => {
+            *game_state = GameState::KeepGoing;
+        },
+        Some(OverlayButton::TryAgain) | Some(OverlayButton::NewGame) => {
+            *game_state = GameState::Playing;
+
+            new_game_events.send(NewGameEvent);
+        },
+        Some(OverlayButton::Continue) => {
+            *game_state = GameState::resumed_on(&game_board);
+        },
+        None => {},
+    }
+}
+
+/// Removes the overlays once the game takes input again, whether a button
+/// was pressed or the last move was undone.
+pub fn overlay_cleaner(
+    commands: &mut Commands,
+    game_state: ChangedRes<GameState>,
+    overlays: Query<Entity, With<Overlay>>,
+) {
+    if !game_state.accepts_input() {
+        return;
+    }
+
+    for entity in overlays.iter() {
+        commands.despawn_recursive(entity);
+    }
+}
+
+fn spawn_overlay(
+    commands: &mut Commands,
+    font: Handle<Font>,
+    materials: &Materials,
+    layout: &BoardLayout,
+    title: &str,
+    subtitle: String,
+    buttons: &[OverlayButton],
+) {
+    commands.spawn(NodeBundle {
+        style: Style {
+            size: Size {
+                height: Val::Px(layout.board_height()),
+                width: Val::Px(layout.window_width()),
+            },
+            // stretch has a flipped y-axis, so a reversed column reads top to bottom
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            position_type: PositionType::Absolute,
+            position: Rect {
+                left: Val::Px(0.0),
+                top: Val::Px(SCORE_PANEL_HEIGHT),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        material: materials.overlay_color.clone(),
+        ..Default::default()
+    })
+    .with(Overlay)
+    .with_children(|parent| {
+        parent.spawn(overlay_text(title.to_string(), font.clone(), OVERLAY_TITLE_TEXT_SIZE, materials.text_primary_color));
+        parent.spawn(overlay_text(subtitle, font.clone(), SCORE_TEXT_SIZE, materials.text_primary_color));
+
+        for button in buttons.iter() {
+            parent.spawn(ButtonBundle {
+                style: Style {
+                    size: Size {
+                        height: Val::Px(OVERLAY_BUTTON_HEIGHT),
+                        width: Val::Px(OVERLAY_BUTTON_WIDTH),
+                    },
+                    margin: Rect::all(Val::Px(GAP / 4.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                material: materials.score_box_color.clone(),
+                ..Default::default()
+            })
+            .with(*button)
+            .with_children(|parent| {
+                parent.spawn(overlay_text(button.label().to_string(), font.clone(), SCORE_TEXT_SIZE, materials.text_inverted_color));
+            });
+        }
+    });
+}
+
+fn overlay_text(value: String, font: Handle<Font>, font_size: f32, color: Color) -> TextBundle {
+    TextBundle {
+        text: Text {
+            value,
+            font,
+            style: TextStyle {
+                font_size,
+                color,
+                alignment: TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    vertical: VerticalAlign::Center,
+                },
+            },
+        },
+        style: Style {
+            margin: Rect::all(Val::Px(GAP / 4.0)),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
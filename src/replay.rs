@@ -0,0 +1,535 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use game_2048_engine::{BlockSize, GameBoard, GameMovementDirection, SpawnPolicy};
+use serde::{Deserialize, Serialize};
+
+use crate::events::*;
+use crate::save::{numbered_tile_ids, SaveData, SAVE_VERSION};
+use crate::{GameMovement, GameState};
+
+/// Version of the replay format written by this build. Version 1 replays,
+/// which have no tile IDs, still play.
+pub const REPLAY_VERSION: u64 = 2;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReplaySpawn {
+    pub x: u8,
+    pub y: u8,
+    pub value: u32,
+}
+
+/// A move and the blocks that appeared after it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ReplayMove {
+    pub direction: GameMovementDirection,
+    pub spawns: Vec<ReplaySpawn>,
+}
+
+impl ReplayMove {
+    /// Tells whether the board shows the blocks spawned after this move.
+    pub fn spawned_on(&self, game_board: &GameBoard) -> bool {
+        self.spawns.iter().all(|spawn| {
+            game_board.get_cell(spawn.x, spawn.y).map(BlockSize::value) == Some(spawn.value)
+        })
+    }
+}
+
+/// A recorded game: the board it starts from, the rules and generator state
+/// that spawn its blocks, and every move made from there.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub version: u64,
+    pub width: u8,
+    pub height: u8,
+    pub seed: u64,
+    /// Generator position in the stream of `seed` when the recording started.
+    pub rng_position: u128,
+    /// Block values paired with their weights.
+    pub spawn_distribution: Vec<(u32, u32)>,
+    pub blocks_per_move: u8,
+    /// Block values row by row, `0` for an empty cell.
+    pub board: Vec<Vec<u32>>,
+    /// The IDs of the blocks of `board`, so a replay names every block the
+    /// way the recorded game did. Empty in version 1 replays.
+    #[serde(default)]
+    pub tile_ids: Vec<Vec<u32>>,
+    #[serde(default)]
+    pub next_tile_id: u32,
+    pub score: u32,
+    pub moves: Vec<ReplayMove>,
+}
+
+impl Replay {
+    pub fn starting_from(game_board: &GameBoard, spawn_policy: &SpawnPolicy) -> Self {
+        let start = SaveData::from_game_board(game_board);
+
+        Replay {
+            version: REPLAY_VERSION,
+            width: start.width,
+            height: start.height,
+            seed: start.seed,
+            rng_position: start.rng_position,
+            spawn_distribution: spawn_policy.distribution.iter()
+                .map(|(block_size, weight)| (block_size.value(), *weight))
+                .collect(),
+            blocks_per_move: spawn_policy.blocks_per_move,
+            board: start.board,
+            tile_ids: start.tile_ids,
+            next_tile_id: start.next_tile_id,
+            score: start.score,
+            moves: Vec::new(),
+        }
+    }
+
+    /// The board the recording starts from, spawning the blocks it recorded.
+    pub fn start_board(&self) -> Result<GameBoard, String> {
+        let (tile_ids, next_tile_id) = match self.version {
+            1 => numbered_tile_ids(&self.board),
+            _ => (self.tile_ids.clone(), self.next_tile_id),
+        };
+
+        SaveData {
+            version: SAVE_VERSION,
+            width: self.width,
+            height: self.height,
+            board: self.board.clone(),
+            tile_ids,
+            next_tile_id,
+            score: self.score,
+            best_score: self.score,
+            moves: 0,
+            seed: self.seed,
+            rng_position: self.rng_position,
+        }.to_game_board()
+    }
+
+    pub fn spawn_policy(&self) -> Result<SpawnPolicy, String> {
+        let distribution = self.spawn_distribution.iter()
+            .map(|(value, weight)| {
+                BlockSize::from_value(*value)
+                    .map(|block_size| (block_size, *weight))
+                    .ok_or(format!("invalid block: {}", value))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // the board is recorded with its first blocks, so only spawns after moves matter
+        SpawnPolicy::new(distribution, self.blocks_per_move, SpawnPolicy::default().initial_blocks)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path).map_err(|err| err.to_string())?;
+
+        Replay::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let replay: Replay = serde_json::from_str(json).map_err(|err| err.to_string())?;
+
+        if replay.version == 0 || replay.version > REPLAY_VERSION {
+            return Err(format!("unsupported replay version: {}", replay.version));
+        }
+
+        Ok(replay)
+    }
+
+    pub fn store(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+
+        let json = serde_json::to_string(self).expect("replays are always serializable");
+
+        fs::write(path, json).map_err(|err| err.to_string())
+    }
+}
+
+/// `number` tells apart the replays started in the same millisecond,
+/// like a quick restart of a game with a fixed seed.
+fn replay_path(seed: u64, number: u32) -> Option<PathBuf> {
+    let started_at = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_millis();
+
+    dirs::data_dir().map(|dir| dir.join("game_2048").join("replays").join(replay_file_name(started_at, seed, number)))
+}
+
+fn replay_file_name(started_at: u128, seed: u64, number: u32) -> String {
+    format!("{}-{}-{}.json", started_at, seed, number)
+}
+
+struct Recording {
+    replay: Replay,
+    path: Option<PathBuf>,
+    /// Moves made on the board before the recording started.
+    start_moves: u32,
+    /// Undone moves, newest last, for when they are redone.
+    undone: Vec<ReplayMove>,
+}
+
+/// Records the game being played, one replay file per game.
+pub struct ReplayRecorder {
+    recording: Option<Recording>,
+    /// Replays started so far.
+    started: u32,
+    enabled: bool,
+}
+
+impl ReplayRecorder {
+    pub fn new(enabled: bool) -> Self {
+        ReplayRecorder {
+            recording: None,
+            started: 0,
+            enabled,
+        }
+    }
+
+    /// Records a move about to be made on `game_board`. The first move
+    /// of a game starts a new replay from the board as it is before it.
+    pub fn record_move(&mut self, game_board: &GameBoard, spawn_policy: &SpawnPolicy, direction: GameMovementDirection) {
+        if !self.enabled {
+            return;
+        }
+
+        if self.recording.is_none() {
+            self.recording = Some(Recording {
+                replay: Replay::starting_from(game_board, spawn_policy),
+                path: replay_path(game_board.seed(), self.started),
+                start_moves: game_board.moves(),
+                undone: Vec::new(),
+            });
+            self.started += 1;
+        }
+
+        let recording = self.recording.as_mut().unwrap();
+
+        recording.replay.moves.push(ReplayMove {
+            direction,
+            spawns: Vec::new(),
+        });
+        recording.undone.clear();
+    }
+
+    /// Adds blocks spawned after the last recorded move.
+    pub fn record_spawns(&mut self, spawned: &[(BlockSize, (u8, u8))]) {
+        let last_move = self.recording.as_mut().and_then(|recording| recording.replay.moves.last_mut());
+
+        if let Some(last_move) = last_move {
+            last_move.spawns.extend(spawned.iter().map(|(block_size, (x, y))| ReplaySpawn {
+                x: *x,
+                y: *y,
+                value: block_size.value(),
+            }));
+        }
+    }
+
+    /// Follows the board to `moves` made after an undo or a redo. Undoing
+    /// past the start of the recording ends it.
+    pub fn sync_to(&mut self, moves: u32) {
+        let recording = match self.recording.as_mut() {
+            Some(recording) => recording,
+            None => return,
+        };
+
+        if moves < recording.start_moves {
+            self.recording = None;
+
+            return;
+        }
+
+        let recorded = (moves - recording.start_moves) as usize;
+
+        while recording.replay.moves.len() > recorded {
+            let undone = recording.replay.moves.pop().unwrap();
+
+            recording.undone.push(undone);
+        }
+
+        while recording.replay.moves.len() < recorded {
+            match recording.undone.pop() {
+                Some(redone) => recording.replay.moves.push(redone),
+                None => break,
+            }
+        }
+    }
+
+    /// Ends the replay of the current game, the next move starts a new one.
+    pub fn finish(&mut self) {
+        self.recording = None;
+    }
+
+    pub fn store(&self) -> Result<(), String> {
+        match self.recording.as_ref() {
+            Some(Recording { replay, path: Some(path), .. }) => replay.store(path),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Plays a replay back by feeding its moves to the regular movement systems.
+pub struct ReplayPlayer {
+    replay: Replay,
+    next_move: usize,
+    paused: bool,
+    /// Set when the next move is due but the previous one still animates.
+    move_due: bool,
+    timer: Timer,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay, speed: f32) -> Self {
+        ReplayPlayer {
+            replay,
+            next_move: 0,
+            paused: false,
+            move_due: false,
+            timer: Timer::from_seconds(1.0 / speed, true),
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.next_move >= self.replay.moves.len()
+    }
+}
+
+pub fn replay_recorder_watcher(
+    new_game_events: Res<Events<NewGameEvent>>,
+    board_restored_events: Res<Events<BoardRestored>>,
+    game_board: Res<GameBoard>,
+    mut replay_recorder: ResMut<ReplayRecorder>,
+    mut new_game_reader: Local<EventReader<NewGameEvent>>,
+    mut board_restored_reader: Local<EventReader<BoardRestored>>,
+) {
+    if new_game_reader.iter(&new_game_events).next().is_some() {
+        replay_recorder.finish();
+    }
+
+    if board_restored_reader.iter(&board_restored_events).next().is_some() {
+        replay_recorder.sync_to(game_board.moves());
+    }
+}
+
+/// Writes the replay of the current game once the blocks of a move have spawned.
+pub fn replay_writer(
+    replay_recorder: ChangedRes<ReplayRecorder>,
+    game_movement: Res<GameMovement>,
+) {
+    if game_movement.direction.is_some() || game_movement.move_timer.is_some() {
+        return;
+    }
+
+    if let Err(err) = replay_recorder.store() {
+        eprintln!("could not write the replay: {}", err);
+    }
+}
+
+/// Space pauses and resumes the playback, the right arrow steps
+/// through a paused replay one move at a time.
+pub fn replay_player(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    game_board: Res<GameBoard>,
+    game_state: Res<GameState>,
+    mut game_movement: ResMut<GameMovement>,
+    mut replay_player: ResMut<ReplayPlayer>,
+) {
+    if *game_state != GameState::Replaying || replay_player.finished() {
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        replay_player.paused = !replay_player.paused;
+    }
+
+    if replay_player.paused {
+        replay_player.move_due |= keyboard_input.just_pressed(KeyCode::Right);
+    } else {
+        replay_player.timer.tick(time.delta_seconds());
+        replay_player.move_due |= replay_player.timer.finished();
+    }
+
+    if !replay_player.move_due || game_movement.direction.is_some() || game_movement.move_timer.is_some() {
+        return;
+    }
+
+    replay_player.move_due = false;
+
+    if let Some(previous_move) = replay_player.next_move.checked_sub(1).map(|index| &replay_player.replay.moves[index]) {
+        if !previous_move.spawned_on(&game_board) {
+            eprintln!("replay is out of sync after move {}, stopping", replay_player.next_move);
+
+            replay_player.next_move = replay_player.replay.moves.len();
+
+            return;
+        }
+    }
+
+    let direction = replay_player.replay.moves[replay_player.next_move].direction;
+
+    replay_player.next_move += 1;
+
+    game_movement.start(direction);
+}
+
+#[cfg(test)]
+mod replay_tests {
+    use super::*;
+
+    fn recorded(replay_recorder: &ReplayRecorder) -> Option<&Replay> {
+        replay_recorder.recording.as_ref().map(|recording| &recording.replay)
+    }
+
+    /// Plays `directions` the way the game systems do, recording each move.
+    fn record(
+        game_board: &mut GameBoard,
+        spawn_policy: &SpawnPolicy,
+        replay_recorder: &mut ReplayRecorder,
+        directions: &[GameMovementDirection],
+    ) {
+        for direction in directions.iter() {
+            replay_recorder.record_move(game_board, spawn_policy, *direction);
+
+            game_board.apply_move(*direction);
+
+            let spawned = game_board.spawn(spawn_policy, spawn_policy.blocks_per_move);
+
+            replay_recorder.record_spawns(&spawned);
+        }
+    }
+
+    fn recorded_game() -> (GameBoard, Replay) {
+        let spawn_policy = SpawnPolicy {
+            blocks_per_move: 2,
+            ..Default::default()
+        };
+        let mut game_board = GameBoard::with_seed(4, 4, 5);
+        let mut replay_recorder = ReplayRecorder::new(true);
+
+        game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        record(&mut game_board, &spawn_policy, &mut replay_recorder, &[
+            GameMovementDirection::Left,
+            GameMovementDirection::Down,
+            GameMovementDirection::Right,
+            GameMovementDirection::Up,
+        ]);
+
+        (game_board, recorded(&replay_recorder).unwrap().clone())
+    }
+
+    #[test]
+    fn playback() {
+        let (game_board, replay) = recorded_game();
+
+        let replay = Replay::from_json(&serde_json::to_string(&replay).unwrap()).unwrap();
+
+        let spawn_policy = replay.spawn_policy().unwrap();
+        let mut played_board = replay.start_board().unwrap();
+
+        assert_eq!(replay.moves.len(), 4);
+
+        for replay_move in replay.moves.iter() {
+            assert_eq!(replay_move.spawns.len(), 2);
+
+            played_board.apply_move(replay_move.direction);
+            played_board.spawn(&spawn_policy, spawn_policy.blocks_per_move);
+
+            assert!(replay_move.spawned_on(&played_board));
+        }
+
+        let (played, recorded) = (SaveData::from_game_board(&played_board), SaveData::from_game_board(&game_board));
+
+        assert_eq!(played.board, recorded.board);
+        assert_eq!(played.tile_ids, recorded.tile_ids);
+        assert_eq!(played_board.score(), game_board.score());
+    }
+
+    #[test]
+    fn versions() {
+        let (_, replay) = recorded_game();
+
+        let json = serde_json::to_string(&replay).unwrap();
+        let mut older: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        older["version"] = serde_json::Value::from(1);
+        older.as_object_mut().unwrap().remove("tile_ids");
+        older.as_object_mut().unwrap().remove("next_tile_id");
+
+        let older = Replay::from_json(&older.to_string()).unwrap();
+        let start_board = older.start_board().unwrap();
+
+        assert_eq!(SaveData::from_game_board(&start_board).board, replay.board);
+        assert_eq!(start_board.get_tile(0, 0).is_some(), replay.board[0][0] > 0);
+
+        let mut newer: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        newer["version"] = serde_json::Value::from(REPLAY_VERSION + 1);
+
+        assert!(Replay::from_json(&newer.to_string()).is_err());
+    }
+
+    #[test]
+    fn file_names() {
+        assert_eq!(replay_file_name(1_600_000_000_000, 5, 0), "1600000000000-5-0.json");
+
+        let spawn_policy = SpawnPolicy::default();
+        let mut replay_recorder = ReplayRecorder::new(true);
+        let mut paths = Vec::new();
+
+        // the same seed twice in a row, well within a millisecond
+        for _ in 0..2 {
+            let mut game_board = GameBoard::with_seed(4, 4, 5);
+
+            game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+            record(&mut game_board, &spawn_policy, &mut replay_recorder, &[GameMovementDirection::Left]);
+
+            paths.push(replay_recorder.recording.as_ref().unwrap().path.clone());
+            replay_recorder.finish();
+        }
+
+        assert_ne!(paths[0], paths[1]);
+    }
+
+    #[test]
+    fn spawn_policies() {
+        let (_, mut replay) = recorded_game();
+
+        replay.spawn_distribution = vec![(2, 0), (4, 0)];
+        assert!(replay.spawn_policy().is_err());
+
+        replay.spawn_distribution = vec![(2, u32::MAX), (4, u32::MAX)];
+        assert!(replay.spawn_policy().is_ok());
+
+        replay.blocks_per_move = 0;
+        assert!(replay.spawn_policy().is_err());
+    }
+
+    #[test]
+    fn undo_redo() {
+        let spawn_policy = SpawnPolicy::default();
+        let mut game_board = GameBoard::with_seed(4, 4, 5);
+        let mut replay_recorder = ReplayRecorder::new(true);
+
+        game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        let directions = [GameMovementDirection::Left, GameMovementDirection::Down, GameMovementDirection::Right];
+
+        record(&mut game_board, &spawn_policy, &mut replay_recorder, &directions);
+
+        let moves = recorded(&replay_recorder).unwrap().moves.clone();
+
+        replay_recorder.sync_to(1);
+        assert_eq!(recorded(&replay_recorder).unwrap().moves[..], moves[..1]);
+
+        replay_recorder.sync_to(3);
+        assert_eq!(recorded(&replay_recorder).unwrap().moves, moves);
+
+        replay_recorder.finish();
+        assert!(recorded(&replay_recorder).is_none());
+
+        let mut replay_recorder = ReplayRecorder::new(false);
+
+        record(&mut game_board, &spawn_policy, &mut replay_recorder, &directions);
+        assert!(recorded(&replay_recorder).is_none());
+    }
+}
@@ -0,0 +1,320 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use game_2048_engine::{BlockSize, GameBoard, Tile, TileId, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::GameMovement;
+
+/// Version of the save format written by this build. Bump it whenever
+/// `SaveData` changes and add a step to `MIGRATIONS`.
+pub const SAVE_VERSION: u64 = 2;
+
+/// Upgrades a save from the version it is indexed by (starting at 1)
+/// to the next one.
+type Migration = fn(&mut Value) -> Result<(), String>;
+
+const MIGRATIONS: &[Migration] = &[add_tile_ids];
+
+/// Version 2 gives every block an ID.
+fn add_tile_ids(save: &mut Value) -> Result<(), String> {
+    let board: Vec<Vec<u32>> = serde_json::from_value(save["board"].clone()).map_err(|err| err.to_string())?;
+    let (tile_ids, next_tile_id) = numbered_tile_ids(&board);
+
+    save["tile_ids"] = Value::from(tile_ids);
+    save["next_tile_id"] = Value::from(next_tile_id);
+
+    Ok(())
+}
+
+/// IDs for the blocks of a board stored without them, numbered row by row,
+/// and the ID that comes after them.
+pub fn numbered_tile_ids(board: &[Vec<u32>]) -> (Vec<Vec<u32>>, u32) {
+    let mut next_tile_id = TileId::FIRST.0;
+
+    let tile_ids = board.iter()
+        .map(|row| {
+            row.iter()
+                .map(|value| match value {
+                    0 => 0,
+                    _ => {
+                        next_tile_id += 1;
+
+                        next_tile_id - 1
+                    },
+                })
+                .collect()
+        })
+        .collect();
+
+    (tile_ids, next_tile_id)
+}
+
+/// A game in progress as it is written to disk.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct SaveData {
+    pub version: u64,
+    pub width: u8,
+    pub height: u8,
+    /// Block values row by row, `0` for an empty cell. Values rather than
+    /// exponents, so the file does not depend on how blocks are stored.
+    pub board: Vec<Vec<u32>>,
+    /// The ID of the block in each cell, `0` for an empty cell.
+    pub tile_ids: Vec<Vec<u32>>,
+    pub next_tile_id: u32,
+    pub score: u32,
+    pub best_score: u32,
+    pub moves: u32,
+    pub seed: u64,
+    /// Generator position in the stream of `seed`, so the blocks spawned
+    /// after resuming are the ones the game would have spawned anyway.
+    pub rng_position: u128,
+}
+
+impl SaveData {
+    pub fn from_game_board(game_board: &GameBoard) -> Self {
+        let cells = |value: &dyn Fn(Tile) -> u32| -> Vec<Vec<u32>> {
+            (0..game_board.height())
+                .map(|y| {
+                    (0..game_board.width())
+                        .map(|x| game_board.get_tile(x, y).map_or(0, value))
+                        .collect()
+                })
+                .collect()
+        };
+
+        SaveData {
+            version: SAVE_VERSION,
+            width: game_board.width(),
+            height: game_board.height(),
+            board: cells(&|tile| tile.block_size.value()),
+            tile_ids: cells(&|tile| tile.id.0),
+            next_tile_id: game_board.next_tile_id().0,
+            score: game_board.score(),
+            best_score: game_board.best_score(),
+            moves: game_board.moves(),
+            seed: game_board.seed(),
+            rng_position: game_board.rng_position(),
+        }
+    }
+
+    pub fn to_game_board(&self) -> Result<GameBoard, String> {
+        let sizes = MIN_BOARD_SIZE..=MAX_BOARD_SIZE;
+
+        if !sizes.contains(&self.width) || !sizes.contains(&self.height) {
+            return Err(format!("unsupported board size: {}x{}", self.width, self.height));
+        }
+
+        let fits = |cells: &Vec<Vec<u32>>| {
+            cells.len() == self.height as usize && cells.iter().all(|row| row.len() == self.width as usize)
+        };
+
+        if !fits(&self.board) || !fits(&self.tile_ids) {
+            return Err(format!("board does not match its size: {}x{}", self.width, self.height));
+        }
+
+        let mut game_board = GameBoard::with_seed(self.width, self.height, self.seed);
+        let mut seen_ids = Vec::new();
+
+        for (y, (row, ids)) in self.board.iter().zip(self.tile_ids.iter()).enumerate() {
+            for (x, (value, id)) in row.iter().zip(ids.iter()).enumerate() {
+                let tile = match (value, id) {
+                    (0, 0) => None,
+                    (0, _) | (_, 0) => return Err(format!("block and ID do not match: {} and {}", value, id)),
+                    (value, id) => {
+                        if seen_ids.contains(id) || *id >= self.next_tile_id {
+                            return Err(format!("invalid block ID: {}", id));
+                        }
+
+                        seen_ids.push(*id);
+
+                        Some(Tile {
+                            id: TileId(*id),
+                            block_size: BlockSize::from_value(*value).ok_or(format!("invalid block: {}", value))?,
+                        })
+                    },
+                };
+
+                game_board.set_tile(x as u8, y as u8, tile);
+            }
+        }
+
+        game_board.resume(self.score, self.best_score, self.moves, self.rng_position, TileId(self.next_tile_id));
+
+        Ok(game_board)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("save data is always serializable")
+    }
+
+    /// Reads a save of any known version, upgrading older ones step by step.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let mut save: Value = serde_json::from_str(json).map_err(|err| err.to_string())?;
+
+        let version = save["version"].as_u64().ok_or("save has no version")?;
+
+        if version == 0 || version > SAVE_VERSION {
+            return Err(format!("unsupported save version: {}", version));
+        }
+
+        for migration in MIGRATIONS.iter().skip(version as usize - 1) {
+            migration(&mut save)?;
+        }
+
+        save["version"] = Value::from(SAVE_VERSION);
+
+        serde_json::from_value(save).map_err(|err| err.to_string())
+    }
+}
+
+fn save_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("game_2048").join("save.json"))
+}
+
+/// Loads the saved game, if there is one. A save that cannot be read is
+/// reported and ignored, so a broken file never keeps the game from starting.
+pub fn load_game() -> Option<GameBoard> {
+    let path = save_path()?;
+    let json = fs::read_to_string(&path).ok()?;
+
+    match SaveData::from_json(&json).and_then(|save| save.to_game_board()) {
+        Ok(game_board) => Some(game_board),
+        Err(err) => {
+            eprintln!("ignoring saved game {}: {}", path.display(), err);
+
+            None
+        }
+    }
+}
+
+pub fn store_game(game_board: &GameBoard) -> Result<(), String> {
+    let path = save_path().ok_or("no data directory to save the game to")?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+
+    // write next to the save first, so closing the game mid-write keeps the old one
+    let tmp_path = path.with_extension("json.tmp");
+
+    fs::write(&tmp_path, SaveData::from_game_board(game_board).to_json()).map_err(|err| err.to_string())?;
+    fs::rename(&tmp_path, &path).map_err(|err| err.to_string())
+}
+
+/// Saves the game whenever the board settles after a change.
+pub fn game_saver(
+    game_board: ChangedRes<GameBoard>,
+    game_movement: Res<GameMovement>,
+) {
+    // the board is saved once the blocks of a move have spawned
+    if game_movement.direction.is_some() || game_movement.move_timer.is_some() {
+        return;
+    }
+
+    if let Err(err) = store_game(&game_board) {
+        eprintln!("could not save the game: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod save_tests {
+    use game_2048_engine::{GameMovementDirection, SpawnPolicy};
+
+    use super::*;
+
+    fn played_board() -> GameBoard {
+        let mut game_board = GameBoard::with_seed(5, 4, 11);
+        let spawn_policy = SpawnPolicy::default();
+
+        game_board.spawn(&spawn_policy, spawn_policy.initial_blocks);
+
+        for direction in [GameMovementDirection::Left, GameMovementDirection::Up, GameMovementDirection::Right].iter() {
+            game_board.apply_move(*direction);
+            game_board.spawn(&spawn_policy, spawn_policy.blocks_per_move);
+        }
+
+        game_board
+    }
+
+    #[test]
+    fn round_trip() {
+        let spawn_policy = SpawnPolicy::default();
+        let mut game_board = played_board();
+
+        let save = SaveData::from_game_board(&game_board);
+        let mut resumed = SaveData::from_json(&save.to_json()).unwrap().to_game_board().unwrap();
+
+        assert_eq!(SaveData::from_game_board(&resumed), save);
+        assert_eq!(resumed.moves(), 3);
+
+        // the resumed game spawns the same blocks the original one does
+        assert_eq!(
+            resumed.spawn(&spawn_policy, 4),
+            game_board.spawn(&spawn_policy, 4),
+        );
+    }
+
+    #[test]
+    fn versions() {
+        let save = SaveData::from_game_board(&played_board());
+
+        let mut newer: Value = serde_json::from_str(&save.to_json()).unwrap();
+        newer["version"] = Value::from(SAVE_VERSION + 1);
+
+        assert!(SaveData::from_json(&newer.to_string()).is_err());
+        assert!(SaveData::from_json("{\"score\": 4}").is_err());
+    }
+
+    #[test]
+    fn migrations() {
+        let mut save: Value = serde_json::from_str(&SaveData::from_game_board(&played_board()).to_json()).unwrap();
+        let board = save["board"].clone();
+
+        // a save as version 1 wrote it
+        save["version"] = Value::from(1);
+        save.as_object_mut().unwrap().remove("tile_ids");
+        save.as_object_mut().unwrap().remove("next_tile_id");
+
+        let migrated = SaveData::from_json(&save.to_string()).unwrap();
+        let blocks = migrated.board.iter().flatten().filter(|value| **value > 0).count() as u32;
+
+        assert_eq!(migrated.version, SAVE_VERSION);
+        assert_eq!(serde_json::to_value(&migrated.board).unwrap(), board);
+        assert_eq!(migrated.next_tile_id, blocks + 1);
+
+        let game_board = migrated.to_game_board().unwrap();
+        let mut ids: Vec<u32> = migrated.tile_ids.iter().flatten().copied().filter(|id| *id > 0).collect();
+
+        ids.sort_unstable();
+
+        assert_eq!(ids, (1..=blocks).collect::<Vec<_>>());
+        assert_eq!(game_board.next_tile_id(), TileId(blocks + 1));
+    }
+
+    #[test]
+    fn invalid_boards() {
+        let mut save = SaveData::from_game_board(&played_board());
+        let (x, y) = (0..save.width as usize)
+            .flat_map(|x| (0..save.height as usize).map(move |y| (x, y)))
+            .find(|(x, y)| save.board[*y][*x] > 0)
+            .unwrap();
+
+        save.tile_ids[y][x] = save.next_tile_id;
+        assert!(save.to_game_board().is_err());
+
+        save.tile_ids[y][x] = 0;
+        assert!(save.to_game_board().is_err());
+
+        save.board[0][0] = 3;
+        assert!(save.to_game_board().is_err());
+
+        save.board[0] = vec![0; 2];
+        assert!(save.to_game_board().is_err());
+
+        save.width = 12;
+        assert!(save.to_game_board().is_err());
+    }
+}
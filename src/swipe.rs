@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use game_2048_engine::GameMovementDirection;
+
+use crate::actions::InputAction;
+use crate::constants::*;
+
+/// Where the mouse drag being made started, in window coordinates.
+#[derive(Default)]
+pub struct SwipeStart(Option<Vec2>);
+
+/// The move a drag from `start` to `end` asks for, with y growing upwards as
+/// in window coordinates. Drags shorter than `SWIPE_MIN_DISTANCE`, or too
+/// diagonal to tell horizontal from vertical, ask for none.
+pub fn swipe_direction(start: Vec2, end: Vec2) -> Option<GameMovementDirection> {
+    let (dx, dy) = (end.x - start.x, end.y - start.y);
+    let (horizontal, vertical) = (dx.abs(), dy.abs());
+
+    if horizontal.max(vertical) < SWIPE_MIN_DISTANCE {
+        return None;
+    }
+
+    if horizontal >= vertical * SWIPE_AXIS_RATIO {
+        Some(if dx > 0.0 { GameMovementDirection::Right } else { GameMovementDirection::Left })
+    } else if vertical >= horizontal * SWIPE_AXIS_RATIO {
+        Some(if dy > 0.0 { GameMovementDirection::Up } else { GameMovementDirection::Down })
+    } else {
+        None
+    }
+}
+
+/// Turns mouse drags and touch swipes into move actions, once the button
+/// is released or the finger lifted, so they move the blocks the way the
+/// arrow keys do.
+pub fn swipe_input(
+    mouse_button_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    mut swipe_start: Local<SwipeStart>,
+    mut actions: ResMut<Events<InputAction>>,
+) {
+    let cursor_position = windows.get_primary().and_then(|window| window.cursor_position());
+    let mut direction = None;
+
+    if mouse_button_input.just_pressed(MouseButton::Left) {
+        swipe_start.0 = cursor_position;
+    }
+
+    if mouse_button_input.just_released(MouseButton::Left) {
+        // a drag ending outside the window does not count
+        if let (Some(start), Some(end)) = (swipe_start.0.take(), cursor_position) {
+            direction = swipe_direction(start, end);
+        }
+    }
+
+    for touch in touches.iter_just_released() {
+        direction = direction.or_else(|| swipe_direction(touch.start_position(), touch.position()));
+    }
+
+    if let Some(direction) = direction {
+        actions.send(InputAction::Move(direction));
+    }
+}
+
+#[cfg(test)]
+mod swipe_tests {
+    use super::*;
+
+    #[test]
+    fn directions() {
+        let start = Vec2::new(100.0, 100.0);
+
+        assert_eq!(swipe_direction(start, Vec2::new(180.0, 110.0)), Some(GameMovementDirection::Right));
+        assert_eq!(swipe_direction(start, Vec2::new(20.0, 90.0)), Some(GameMovementDirection::Left));
+        assert_eq!(swipe_direction(start, Vec2::new(100.0, 160.0)), Some(GameMovementDirection::Up));
+        assert_eq!(swipe_direction(start, Vec2::new(110.0, 20.0)), Some(GameMovementDirection::Down));
+    }
+
+    #[test]
+    fn short_and_diagonal_drags() {
+        let start = Vec2::new(100.0, 100.0);
+
+        // a click that wobbled
+        assert_eq!(swipe_direction(start, Vec2::new(105.0, 98.0)), None);
+        assert_eq!(swipe_direction(start, Vec2::new(100.0 + SWIPE_MIN_DISTANCE - 1.0, 100.0)), None);
+        assert_eq!(swipe_direction(start, Vec2::new(100.0 + SWIPE_MIN_DISTANCE, 100.0)), Some(GameMovementDirection::Right));
+
+        assert_eq!(swipe_direction(start, Vec2::new(180.0, 170.0)), None);
+        assert_eq!(swipe_direction(start, Vec2::new(20.0, 20.0)), None);
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use game_2048_engine::GameBoard;
 
 use crate::components::*;
 use crate::constants::*;
@@ -57,3 +58,12 @@ fn animate_block(
         style.margin.left = Val::Px(0.0);
     }
 }
+
+pub fn score_panel_updater(
+    game_board: ChangedRes<GameBoard>,
+    mut score_texts: Query<(&mut Text, &ScoreText)>,
+) {
+    for (mut text, score_text) in score_texts.iter_mut() {
+        text.value = score_text.value(&game_board);
+    }
+}
@@ -0,0 +1,207 @@
+use std::io::{self, Write};
+use std::{env, process};
+
+use crossterm::cursor::{Hide, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use game_2048_cli::{parse_board_size, parse_seed};
+use game_2048_engine::{GameBoard, GameMovementDirection, SpawnPolicy, WIN_BLOCK_SIZE};
+
+mod render;
+
+const USAGE: &str = "usage: game_2048_tui [--size <cols>x<rows>] [--seed <number>]";
+const HELP: &str = "arrows, wasd or hjkl: move   r: new game   q: quit";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Command {
+    Move(GameMovementDirection),
+    NewGame,
+    Quit,
+}
+
+fn command_for(key: KeyEvent) -> Option<Command> {
+    let command = match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Command::Quit,
+        KeyCode::Up | KeyCode::Char('w') | KeyCode::Char('k') => Command::Move(GameMovementDirection::Up),
+        KeyCode::Right | KeyCode::Char('d') | KeyCode::Char('l') => Command::Move(GameMovementDirection::Right),
+        KeyCode::Down | KeyCode::Char('s') | KeyCode::Char('j') => Command::Move(GameMovementDirection::Down),
+        KeyCode::Left | KeyCode::Char('a') | KeyCode::Char('h') => Command::Move(GameMovementDirection::Left),
+        KeyCode::Char('r') => Command::NewGame,
+        KeyCode::Char('q') | KeyCode::Esc => Command::Quit,
+        _ => return None,
+    };
+
+    Some(command)
+}
+
+struct Game {
+    game_board: GameBoard,
+    spawn_policy: SpawnPolicy,
+}
+
+impl Game {
+    fn new(game_board: GameBoard) -> Self {
+        let mut game = Game {
+            game_board,
+            spawn_policy: SpawnPolicy::default(),
+        };
+
+        game.new_game();
+
+        game
+    }
+
+    fn new_game(&mut self) {
+        self.game_board.reset();
+        self.game_board.spawn(&self.spawn_policy, self.spawn_policy.initial_blocks);
+    }
+
+    fn play(&mut self, direction: GameMovementDirection) {
+        if self.game_board.apply_move(direction).changed() {
+            self.game_board.spawn(&self.spawn_policy, self.spawn_policy.blocks_per_move);
+        }
+    }
+
+    fn status(&self) -> String {
+        if self.game_board.is_over() {
+            format!("Game over! Score: {}   r: new game   q: quit", self.game_board.score())
+        } else if self.game_board.max_block() >= Some(WIN_BLOCK_SIZE) {
+            format!("You win! Keep going.   {}", HELP)
+        } else {
+            HELP.to_string()
+        }
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<GameBoard, String> {
+    let (mut width, mut height, mut seed) = (4, 4, None);
+
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "--size" => {
+                let value = args.next().ok_or("--size requires a value")?;
+
+                let (cols, rows) = parse_board_size(&value)?;
+
+                width = cols;
+                height = rows;
+            },
+            "--seed" => {
+                let value = args.next().ok_or("--seed requires a value")?;
+
+                seed = Some(parse_seed(&value)?);
+            },
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    Ok(match seed {
+        Some(seed) => GameBoard::with_seed(width, height, seed),
+        None => GameBoard::new(width, height),
+    })
+}
+
+fn run<W: Write>(out: &mut W, game: &mut Game) -> crossterm::Result<()> {
+    loop {
+        render::draw(out, &game.game_board, &game.status())?;
+
+        let key = match event::read()? {
+            Event::Key(key) => key,
+            _ => continue,
+        };
+
+        match command_for(key) {
+            Some(Command::Move(direction)) => game.play(direction),
+            Some(Command::NewGame) => game.new_game(),
+            Some(Command::Quit) => return Ok(()),
+            None => {},
+        }
+    }
+}
+
+fn main() -> crossterm::Result<()> {
+    let game_board = parse_args(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{}\n{}", err, USAGE);
+
+        process::exit(2);
+    });
+
+    let mut game = Game::new(game_board);
+    let mut stdout = io::stdout();
+
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, Hide)?;
+
+    let result = run(&mut stdout, &mut game);
+
+    // leave the terminal usable even when drawing failed
+    execute!(stdout, Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+
+    result?;
+
+    println!("Score: {}", game.game_board.score());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tui_tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn parse(args: &[&str]) -> Result<GameBoard, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn key_bindings() {
+        for code in [KeyCode::Left, KeyCode::Char('a'), KeyCode::Char('h')].iter() {
+            assert_eq!(command_for(key(*code)), Some(Command::Move(GameMovementDirection::Left)));
+        }
+
+        for code in [KeyCode::Up, KeyCode::Char('w'), KeyCode::Char('k')].iter() {
+            assert_eq!(command_for(key(*code)), Some(Command::Move(GameMovementDirection::Up)));
+        }
+
+        assert_eq!(command_for(key(KeyCode::Char('r'))), Some(Command::NewGame));
+        assert_eq!(command_for(key(KeyCode::Esc)), Some(Command::Quit));
+        assert_eq!(command_for(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)), Some(Command::Quit));
+        assert_eq!(command_for(key(KeyCode::Char('c'))), None);
+    }
+
+    #[test]
+    fn arguments() {
+        let game_board = parse(&["--size", "5x3", "--seed", "9"]).unwrap();
+
+        assert_eq!((game_board.width(), game_board.height(), game_board.seed()), (5, 3, 9));
+
+        assert!(parse(&["--size", "9x9"]).is_err());
+        assert!(parse(&["--size", "4"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+        assert!(parse(&["--fast"]).is_err());
+    }
+
+    #[test]
+    fn playing() {
+        let mut game = Game::new(GameBoard::with_seed(4, 4, 1));
+
+        assert_eq!(game.game_board.available_cells().len(), 14);
+        assert_eq!(game.status(), HELP);
+
+        for direction in GameMovementDirection::ALL.iter().cycle().take(200) {
+            game.play(*direction);
+        }
+
+        assert!(game.game_board.score() > 0);
+
+        game.new_game();
+
+        assert_eq!(game.game_board.score(), 0);
+        assert_eq!(game.game_board.available_cells().len(), 14);
+    }
+}
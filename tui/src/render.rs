@@ -0,0 +1,136 @@
+use std::io::Write;
+
+use crossterm::cursor::MoveTo;
+use crossterm::queue;
+use crossterm::style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{Clear, ClearType};
+use game_2048_engine::palette::{self, Rgb};
+use game_2048_engine::{BlockSize, GameBoard};
+
+/// Wide enough for a 131072 block with a space on each side.
+const CELL_WIDTH: usize = 8;
+/// Lines per cell, the value sits on the middle one.
+const CELL_HEIGHT: usize = 3;
+
+/// Picks the closest colour of the xterm 256-colour palette:
+/// either from its 6x6x6 colour cube or from its grey ramp.
+pub fn ansi256((r, g, b): Rgb) -> u8 {
+    const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    let distance = |(r2, g2, b2): Rgb| {
+        let d = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+
+        d(r, r2) + d(g, g2) + d(b, b2)
+    };
+
+    let cube_level = |component: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|level| (i32::from(CUBE_LEVELS[*level]) - i32::from(component)).abs())
+            .unwrap()
+    };
+
+    let (ri, gi, bi) = (cube_level(r), cube_level(g), cube_level(b));
+    let cube_color = (CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+    let cube_index = 16 + 36 * ri + 6 * gi + bi;
+
+    // the grey ramp runs from 8 to 238 in steps of 10
+    let average = (u32::from(r) + u32::from(g) + u32::from(b)) / 3;
+    let grey_step = (average.saturating_sub(3) / 10).min(23);
+    let grey = (8 + grey_step * 10) as u8;
+    let grey_index = 232 + grey_step as usize;
+
+    if distance((grey, grey, grey)) < distance(cube_color) {
+        grey_index as u8
+    } else {
+        cube_index as u8
+    }
+}
+
+fn color(rgb: Rgb) -> Color {
+    Color::AnsiValue(ansi256(rgb))
+}
+
+/// The text of one line of a cell, centered in the cell width.
+fn cell_line(cell: Option<BlockSize>, line: usize) -> String {
+    match cell {
+        Some(block_size) if line == CELL_HEIGHT / 2 => format!("{:^width$}", block_size.to_string(), width = CELL_WIDTH),
+        _ => " ".repeat(CELL_WIDTH),
+    }
+}
+
+/// Draws the board the way `GameBoard::pretty_string` lays it out, a row of
+/// cells per board row, with the tiles in their classic colours.
+pub fn draw<W: Write>(out: &mut W, game_board: &GameBoard, status: &str) -> crossterm::Result<()> {
+    let board_width = game_board.width() as usize * (CELL_WIDTH + 1) + 1;
+    let gap_line = " ".repeat(board_width);
+
+    queue!(
+        out,
+        Clear(ClearType::All),
+        MoveTo(0, 0),
+        Print(format!("Score: {}   Best: {}\r\n\r\n", game_board.score(), game_board.best_score())),
+        SetBackgroundColor(color(palette::BOARD_COLOR)),
+        Print(&gap_line),
+        ResetColor,
+        Print("\r\n"),
+    )?;
+
+    for y in 0..game_board.height() {
+        for line in 0..CELL_HEIGHT {
+            queue!(out, SetBackgroundColor(color(palette::BOARD_COLOR)), Print(" "))?;
+
+            for x in 0..game_board.width() {
+                let cell = game_board.get_cell(x, y);
+
+                let (background, foreground) = match cell {
+                    Some(block_size) => (palette::block_color(block_size), palette::block_text_color(block_size)),
+                    None => (palette::EMPTY_CELL_COLOR, palette::TEXT_PRIMARY_COLOR),
+                };
+
+                queue!(
+                    out,
+                    SetBackgroundColor(color(background)),
+                    SetForegroundColor(color(foreground)),
+                    Print(cell_line(cell, line)),
+                    SetBackgroundColor(color(palette::BOARD_COLOR)),
+                    Print(" "),
+                )?;
+            }
+
+            queue!(out, ResetColor, Print("\r\n"))?;
+        }
+
+        queue!(out, SetBackgroundColor(color(palette::BOARD_COLOR)), Print(&gap_line), ResetColor, Print("\r\n"))?;
+    }
+
+    queue!(out, Print(format!("\r\n{}\r\n", status)))?;
+
+    out.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod render_tests {
+    use super::*;
+
+    #[test]
+    fn ansi_colors() {
+        assert_eq!(ansi256((0, 0, 0)), 16);
+        assert_eq!(ansi256((255, 255, 255)), 231);
+        assert_eq!(ansi256((255, 0, 0)), 196);
+        assert_eq!(ansi256((128, 128, 128)), 244);
+
+        // the light 2 tile is closest to a light grey, the orange 8 tile to an orange
+        assert_eq!(ansi256(palette::block_color(BlockSize::MIN)), 254);
+        assert_eq!(ansi256(palette::block_color(BlockSize::from_value(8).unwrap())), 216);
+    }
+
+    #[test]
+    fn cell_lines() {
+        assert_eq!(cell_line(None, 1), "        ");
+        assert_eq!(cell_line(Some(BlockSize::MIN), 0), "        ");
+        assert_eq!(cell_line(Some(BlockSize::MIN), 1), "   2    ");
+        assert_eq!(cell_line(BlockSize::from_value(131072), 1), " 131072 ");
+    }
+}